@@ -0,0 +1,382 @@
+// Headless front-end for scripts and SSH sessions. Drives the same scanner,
+// planner and executor as the Tauri app, without opening a window.
+use app_lib::classifier::{Explanation, ProjectVerdict};
+use app_lib::config::{default_config_dir, load_config, review_path, save_config};
+use app_lib::executor::undo_batch;
+use app_lib::journal::Journal;
+use app_lib::duplicates::generate_dedup_plan;
+use app_lib::models::{Config, DedupMode, HistoryEntry, IncompleteBatch, OperationType, Plan, RecoveryAction, ScanResult};
+use app_lib::planner::generate_plan;
+use app_lib::scanner::Scanner;
+use app_lib::wal::{apply_logged, recover, WriteAheadLog};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "desktop-organizer", version, about = "Scan, plan and organize a directory without the GUI")]
+struct Cli {
+    /// Directory holding config.json (defaults to the app's config dir)
+    #[arg(long, global = true)]
+    config_dir: Option<PathBuf>,
+
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t = Format::Table)]
+    format: Format,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    Table,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the loose files and projects found under ROOT
+    Scan { root: PathBuf },
+    /// Show why PATH is (or is not) classified as a project, program or app data
+    Explain {
+        path: PathBuf,
+        /// Folder the scan would start from (defaults to PATH's parent)
+        #[arg(long)]
+        root: Option<PathBuf>,
+    },
+    /// Remember whether PATH, listed under "review" by `scan`, is a project
+    #[command(group(ArgGroup::new("decision").required(true)))]
+    Review {
+        path: PathBuf,
+        /// Organize it as a project from now on
+        #[arg(long, group = "decision")]
+        confirm: bool,
+        /// Treat it as a plain folder from now on
+        #[arg(long, group = "decision")]
+        reject: bool,
+    },
+    /// Show the operations that would organize ROOT
+    Plan { root: PathBuf },
+    /// Show a plan that removes duplicate files under ROOT, keeping one copy of each
+    Dedup {
+        root: PathBuf,
+        /// Replace duplicates with hard links instead of trashing them
+        #[arg(long)]
+        hard_link: bool,
+    },
+    /// Organize ROOT (or apply a plan saved with `plan --format json`)
+    Apply {
+        root: Option<PathBuf>,
+        /// Apply this plan file instead of generating a new one
+        #[arg(long, conflicts_with = "root")]
+        plan: Option<PathBuf>,
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Undo the last applied batch (or the one a plan file was applied in)
+    Undo {
+        /// Undo the batch this plan file was applied in instead of the last one
+        plan: Option<PathBuf>,
+    },
+    /// List applied batches that can be undone, oldest first
+    History,
+    /// List batches interrupted mid-apply, or finish one of them
+    Recover {
+        /// Batch to resolve (see `recover` without arguments)
+        batch_id: Option<String>,
+        /// Apply the operations that had not run yet
+        #[arg(long, requires = "batch_id", conflicts_with = "back")]
+        forward: bool,
+        /// Revert the operations that had already run
+        #[arg(long, requires = "batch_id")]
+        back: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    let config_dir = cli.config_dir.or_else(default_config_dir)
+        .ok_or_else(|| "Could not determine the config directory, pass --config-dir".to_string())?;
+    let config = load_config(&config_dir)?;
+    let journal = Journal::new(&config_dir);
+    let wal = WriteAheadLog::new(&config_dir);
+
+    if !matches!(cli.command, Command::Recover { .. }) {
+        let incomplete = wal.incomplete_batches()?;
+        if !incomplete.is_empty() {
+            eprintln!("warning: {} batch(es) were interrupted mid-apply; run `desktop-organizer recover`", incomplete.len());
+        }
+    }
+
+    match cli.command {
+        Command::Scan { root } => {
+            let result = scan(&root, &config)?;
+            match cli.format {
+                Format::Json => print_json(&result),
+                Format::Table => print_scan_table(&result),
+            }
+        }
+        Command::Explain { path, root } => {
+            let root = root.or_else(|| path.parent().map(Path::to_path_buf)).unwrap_or_else(|| path.clone());
+            let explanation = Scanner::new(config).explain(&root, &path);
+            match cli.format {
+                Format::Json => print_json(&explanation),
+                Format::Table => print_explanation(&explanation),
+            }
+        }
+        Command::Review { path, confirm, .. } => {
+            let mut config = config;
+            let path = review_path(&path);
+            config.confirmed_projects.retain(|p| review_path(p) != path);
+            config.rejected_projects.retain(|p| review_path(p) != path);
+            if confirm {
+                config.confirmed_projects.push(path);
+            } else {
+                config.rejected_projects.push(path);
+            }
+            save_config(&config_dir, &config)?;
+        }
+        Command::Plan { root } => {
+            let plan = build_plan(&root, &config)?;
+            match cli.format {
+                Format::Json => print_json(&plan),
+                Format::Table => print_plan_table(&plan, &root),
+            }
+        }
+        Command::Dedup { root, hard_link } => {
+            let files = scan(&root, &config)?.files;
+            let mode = if hard_link { DedupMode::HardLink } else { DedupMode::Trash };
+            let plan = generate_dedup_plan(&files, &root, mode);
+            match cli.format {
+                Format::Json => print_json(&plan),
+                Format::Table => print_plan_table(&plan, &root),
+            }
+        }
+        Command::Apply { root, plan, yes } => {
+            let plan = match (root, plan) {
+                (_, Some(plan_path)) => read_plan(&plan_path)?,
+                (Some(root), None) => build_plan(&root, &config)?,
+                (None, None) => return Err("either ROOT or --plan is required".to_string()),
+            };
+            if plan.operations.is_empty() {
+                println!("Nothing to do.");
+                return Ok(());
+            }
+            if cli.format == Format::Table {
+                print_plan_table(&plan, plan.root.as_deref().unwrap_or(Path::new("")));
+            }
+            if !yes && !confirm(&format!("Apply {} operations?", plan.operations.len()))? {
+                println!("Aborted.");
+                return Ok(());
+            }
+            let entry = apply_logged(&wal, &journal, &plan)?;
+            match cli.format {
+                Format::Json => print_json(&entry),
+                Format::Table => println!("Applied {} operations.", entry.operations.len()),
+            }
+        }
+        Command::Undo { plan } => {
+            // A plan file only says what was intended; the history says what was done.
+            let entry = match plan {
+                Some(plan_path) => journal.find_plan(&read_plan(&plan_path)?)?
+                    .ok_or_else(|| format!("{} was not applied, or was already undone", plan_path.display()))?,
+                None => journal.last()?.ok_or_else(|| "Nothing to undo".to_string())?,
+            };
+            undo_batch(&entry.operations)?;
+            journal.remove(&entry.id)?;
+            println!("Undid {} operations from {}.", entry.operations.len(), entry.timestamp.format("%Y-%m-%d %H:%M:%S"));
+        }
+        Command::History => {
+            let entries = journal.entries()?;
+            match cli.format {
+                Format::Json => print_json(&entries),
+                Format::Table => print_history_table(&entries),
+            }
+        }
+        Command::Recover { batch_id: None, .. } => {
+            let incomplete = wal.incomplete_batches()?;
+            match cli.format {
+                Format::Json => print_json(&incomplete),
+                Format::Table => print_incomplete_table(&incomplete),
+            }
+        }
+        Command::Recover { batch_id: Some(id), forward, back } => {
+            let action = match (forward, back) {
+                (true, false) => RecoveryAction::RollForward,
+                (false, true) => RecoveryAction::RollBack,
+                _ => return Err("pass --forward or --back".to_string()),
+            };
+            println!("{}", recover(&wal, &journal, &id, action)?);
+        }
+    }
+    Ok(())
+}
+
+fn scan(root: &Path, config: &Config) -> Result<ScanResult, String> {
+    if !root.is_dir() {
+        return Err(format!("{} is not a directory", root.display()));
+    }
+    let mut scanner = Scanner::new(config.clone());
+    scanner.scan(root);
+    Ok(scanner.get_results())
+}
+
+fn build_plan(root: &Path, config: &Config) -> Result<Plan, String> {
+    let result = scan(root, config)?;
+    generate_plan(&result.files, &result.projects, config, root)
+}
+
+fn read_plan(path: &Path) -> Result<Plan, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn confirm(prompt: &str) -> Result<bool, String> {
+    use std::io::Write;
+    print!("{} [y/N] ", prompt);
+    std::io::stdout().flush().map_err(|e| e.to_string())?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).map_err(|e| e.to_string())?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn print_json<T: serde::Serialize>(value: &T) {
+    // Serializing our own models cannot fail.
+    println!("{}", serde_json::to_string_pretty(value).unwrap_or_default());
+}
+
+fn print_scan_table(result: &ScanResult) {
+    let rows: Vec<[String; 3]> = result.projects.iter()
+        .map(|p| ["project".to_string(), p.type_guess.clone(), p.path.display().to_string()])
+        .chain(result.needs_review.iter().map(|r| [
+            format!("review {:.2}", r.confidence),
+            r.project.type_guess.clone(),
+            r.project.path.display().to_string(),
+        ]))
+        .chain(result.files.iter().map(|f| ["file".to_string(), f.extension.clone().unwrap_or_default(), f.path.display().to_string()]))
+        .collect();
+    print_table(["KIND", "TYPE", "PATH"], &rows);
+    println!("\n{} files, {} projects", result.files.len(), result.projects.len());
+    if !result.needs_review.is_empty() {
+        println!("{} possible projects left alone; confirm or reject them with `review PATH`", result.needs_review.len());
+    }
+}
+
+fn print_explanation(explanation: &Explanation) {
+    let mut rows = Vec::new();
+    for step in &explanation.classifiers {
+        let outcome = match (&step.outcome, step.decided) {
+            (Some(c), true) => format!("{:?} {:.2} (decides)", c.category, c.confidence),
+            (Some(c), false) => format!("{:?} {:.2} (outranked)", c.category, c.confidence),
+            (None, _) => "no match".to_string(),
+        };
+        rows.push([step.classifier.clone(), step.priority.to_string(), String::new(), outcome]);
+        for check in &step.checks {
+            let result = match (check.matched, check.confidence) {
+                (true, Some(confidence)) => format!("yes {:.2}", confidence),
+                (true, None) => "yes".to_string(),
+                (false, _) => "no".to_string(),
+            };
+            rows.push([String::new(), String::new(), check.what.clone(), result]);
+        }
+    }
+    print_table(["CLASSIFIER", "PRIORITY", "CHECK", "RESULT"], &rows);
+
+    println!();
+    if let Some(reason) = &explanation.excluded_by {
+        println!("Not reached by a scan: {}", reason);
+    }
+    let result = &explanation.result;
+    println!("{}: {:?} - {}", explanation.path.display(), result.category, result.reason);
+    match explanation.verdict {
+        Some(ProjectVerdict::Accepted) | None => {}
+        Some(ProjectVerdict::Confirmed) => println!("Below the confidence threshold, but confirmed as a project"),
+        Some(ProjectVerdict::NeedsReview) => println!("Below the confidence threshold: listed for review and not moved"),
+        Some(ProjectVerdict::Rejected) => println!("Rejected as a project: scanned as a plain folder"),
+    }
+}
+
+fn print_plan_table(plan: &Plan, root: &Path) {
+    // Paths are shown relative to the root to keep rows readable.
+    let rel = |p: &Path| p.strip_prefix(root).unwrap_or(p).display().to_string();
+    let mut rows: Vec<[String; 4]> = Vec::new();
+    for op in &plan.operations {
+        rows.push([
+            op_label(&op.op_type).to_string(),
+            op.source.as_deref().map(rel).unwrap_or_default(),
+            rel(&op.destination),
+            op.reason.clone(),
+        ]);
+        // Under the operation they belong to.
+        rows.extend(op.warnings.iter().map(|w| [String::new(), String::new(), String::new(), format!("warning: {}", w)]));
+    }
+    print_table(["OP", "SOURCE", "DESTINATION", "REASON"], &rows);
+    println!("\n{}", plan.summary);
+}
+
+fn print_history_table(entries: &[HistoryEntry]) {
+    let rows: Vec<[String; 4]> = entries.iter()
+        .map(|e| [
+            e.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            e.root.as_deref().map(|r| r.display().to_string()).unwrap_or_default(),
+            e.operations.len().to_string(),
+            e.summary.clone(),
+        ])
+        .collect();
+    print_table(["APPLIED (UTC)", "ROOT", "OPS", "SUMMARY"], &rows);
+}
+
+fn print_incomplete_table(batches: &[IncompleteBatch]) {
+    let rows: Vec<[String; 5]> = batches.iter()
+        .map(|b| [
+            b.id.clone(),
+            b.started.format("%Y-%m-%d %H:%M:%S").to_string(),
+            b.root.as_deref().map(|r| r.display().to_string()).unwrap_or_default(),
+            b.completed.len().to_string(),
+            (b.pending.len() + usize::from(b.in_flight.is_some())).to_string(),
+        ])
+        .collect();
+    print_table(["BATCH", "STARTED (UTC)", "ROOT", "DONE", "LEFT"], &rows);
+}
+
+fn op_label(op_type: &OperationType) -> &'static str {
+    match op_type {
+        OperationType::Move => "move",
+        OperationType::Copy => "copy",
+        OperationType::Delete => "delete",
+        OperationType::CreateDir => "mkdir",
+        OperationType::Trash => "trash",
+        OperationType::HardLink => "link",
+    }
+}
+
+fn print_table<const N: usize>(headers: [&str; N], rows: &[[String; N]]) {
+    let mut widths = headers.map(|h| h.chars().count());
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells.iter().zip(widths)
+            .map(|(c, w)| format!("{:<width$}", c, width = w))
+            .collect();
+        println!("{}", padded.join("  ").trim_end());
+    };
+    line(headers.to_vec());
+    for row in rows {
+        line(row.iter().map(String::as_str).collect());
+    }
+}
@@ -1,20 +1,19 @@
-use std::path::Path;
-use std::fs;
+use crate::listing::{DirListing, EntryKind};
+use crate::models::{Config, CustomClassifier};
+use std::fs::File;
+use std::io::Read;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum Category {
-    Project,
-    InstalledProgram,
-    AppData,
-    LooseFiles,
-}
+pub use crate::models::Category;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Classification {
     pub category: Category,
     pub confidence: f32, // 0.0 to 1.0
     pub reason: String,
     pub project_type: Option<String>,
+    pub alternatives: Vec<TypeCandidate>, // Other matching project types, best first
 }
 
 impl Default for Classification {
@@ -24,112 +23,427 @@ impl Default for Classification {
             confidence: 0.0,
             reason: "Default".to_string(),
             project_type: None,
+            alternatives: Vec::new(),
         }
     }
 }
 
-pub fn classify_folder(path: &Path, project_markers: &[String]) -> Classification {
-    // 1. Check for Installed Software (High Confidence Safety)
-    if is_installed_software(path) {
-        return Classification {
+/// How much a marker says about what kind of project a folder is. A Vite app
+/// under git is a Vite project first, a JavaScript one second and a repository last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum MarkerKind {
+    Vcs, // Version control and editor metadata
+    Language, // Manifests, build files and source files; also unknown markers
+    Framework,
+}
+
+/// A project marker found in a folder.
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeCandidate {
+    pub marker: String,
+    pub kind: MarkerKind,
+    pub confidence: f32,
+}
+
+/// One way of recognising what a folder is. Classifiers are asked in priority
+/// order (highest first) and the first one that answers decides.
+pub trait FolderClassifier: Send + Sync {
+    fn name(&self) -> &str;
+    fn priority(&self) -> i32;
+    /// `None` if this classifier has no opinion about `dir`.
+    fn classify(&self, dir: &DirListing) -> Option<Classification>;
+    /// Everything this classifier looks at for `dir` and whether it matched. Only
+    /// used to explain a decision, so it may do more work than `classify`.
+    fn checks(&self, _dir: &DirListing) -> Vec<Check> {
+        Vec::new()
+    }
+}
+
+/// One marker or heuristic a classifier tested.
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub what: String,
+    pub matched: bool,
+    pub confidence: Option<f32>, // What a match gives
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClassifierTrace {
+    pub classifier: String,
+    pub priority: i32,
+    pub checks: Vec<Check>,
+    pub outcome: Option<Classification>,
+    pub decided: bool, // The first classifier with an outcome decides
+}
+
+// Built-in priorities: safety checks first, so nothing installed is ever moved as a project.
+pub const INSTALLED_SOFTWARE_PRIORITY: i32 = 300;
+pub const APP_DATA_PRIORITY: i32 = 200;
+pub const PROJECT_MARKER_PRIORITY: i32 = 100;
+
+pub struct InstalledSoftwareClassifier;
+
+impl FolderClassifier for InstalledSoftwareClassifier {
+    fn name(&self) -> &str { "installed-software" }
+    fn priority(&self) -> i32 { INSTALLED_SOFTWARE_PRIORITY }
+
+    fn classify(&self, dir: &DirListing) -> Option<Classification> {
+        first_signal(INSTALLED_SOFTWARE_SIGNALS, dir).map(|reason| Classification {
             category: Category::InstalledProgram,
             confidence: 0.9,
-            reason: "Contains software binary indicators (Uninstall.exe, DLL+EXE)".to_string(),
-            project_type: None,
-        };
+            reason,
+            ..Classification::default()
+        })
     }
 
-    // 2. Check for App Data / Games (Medium-High)
-    if is_app_data(path) {
-        return Classification {
+    fn checks(&self, dir: &DirListing) -> Vec<Check> {
+        signal_checks(INSTALLED_SOFTWARE_SIGNALS, dir, 0.9)
+    }
+}
+
+pub struct AppDataClassifier;
+
+impl FolderClassifier for AppDataClassifier {
+    fn name(&self) -> &str { "app-data" }
+    fn priority(&self) -> i32 { APP_DATA_PRIORITY }
+
+    fn classify(&self, dir: &DirListing) -> Option<Classification> {
+        first_signal(APP_DATA_SIGNALS, dir).map(|reason| Classification {
             category: Category::AppData,
             confidence: 0.8,
-            reason: "Contains game data folder signatures".to_string(),
-            project_type: None,
-        };
+            reason,
+            ..Classification::default()
+        })
+    }
+
+    fn checks(&self, dir: &DirListing) -> Vec<Check> {
+        signal_checks(APP_DATA_SIGNALS, dir, 0.8)
     }
+}
+
+pub struct ProjectMarkerClassifier {
+    pub markers: Vec<String>,
+}
+
+impl FolderClassifier for ProjectMarkerClassifier {
+    fn name(&self) -> &str { "project-markers" }
+    fn priority(&self) -> i32 { PROJECT_MARKER_PRIORITY }
 
-    // 3. Check for Project (Strong Markers vs Weak Markers)
-    if let Some((ptype, confidence)) = detect_project(path, project_markers) {
-        // If confidence is low (Weak Marker) and it looks slightly 'software-ish' (e.g. has a random DLL),
-        // we might want to downgrade or reject? 
-        // For now, if detect_project returns something, we trust it, but we can refine confidence.
-        return Classification {
+    fn classify(&self, dir: &DirListing) -> Option<Classification> {
+        let mut found = detect_project(dir, &self.markers).into_iter();
+        let best = found.next()?;
+        let alternatives: Vec<TypeCandidate> = found.collect();
+        let mut reason = format!("Detected marker: {}", best.marker);
+        if !alternatives.is_empty() {
+            let others: Vec<&str> = alternatives.iter().map(|c| c.marker.as_str()).collect();
+            reason.push_str(&format!(" (also {})", others.join(", ")));
+        }
+        Some(Classification {
             category: Category::Project,
-            confidence,
-            reason: format!("Detected marker: {}", ptype),
-            project_type: Some(ptype),
-        };
+            // How sure we are it is a project at all, whichever marker names the type.
+            confidence: alternatives.iter().map(|c| c.confidence).fold(best.confidence, f32::max),
+            reason,
+            project_type: Some(best.marker),
+            alternatives,
+        })
     }
 
-    // 4. Fallback
-    Classification::default()
+    fn checks(&self, dir: &DirListing) -> Vec<Check> {
+        project_marker_candidates(&self.markers).into_iter()
+            .map(|c| Check {
+                matched: dir.has_marker(&c.marker),
+                what: format!("{} ({:?})", c.marker, c.kind),
+                confidence: Some(c.confidence),
+            })
+            .collect()
+    }
 }
 
-fn is_installed_software(dir: &Path) -> bool {
-    let indicators = [
-        "Uninstall.exe", "unins000.exe", "unins001.exe", "setup.exe",
-        "UnityCrashHandler64.exe", "UnityPlayer.dll",
-        "d3dcompiler_47.dll", "opengl32.dll",
-        "steam_api.dll", "steam_api64.dll",
-        "Galaxy64.dll", "tier0.dll",
-        "adb.exe", "AdbWinApi.dll", // Tools
-    ];
+/// A user-declared layout from `Config.custom_classifiers`.
+pub struct ConfigClassifier {
+    pub spec: CustomClassifier,
+}
 
-    for indicator in indicators {
-        if dir.join(indicator).exists() {
-            return true;
+impl FolderClassifier for ConfigClassifier {
+    fn name(&self) -> &str { &self.spec.name }
+    fn priority(&self) -> i32 { self.spec.priority }
+
+    fn classify(&self, dir: &DirListing) -> Option<Classification> {
+        let spec = &self.spec;
+        if !spec.contains.iter().all(|m| dir.has_marker(m)) || spec.excludes.iter().any(|m| dir.has_marker(m)) {
+            return None;
+        }
+        let mut reason = format!("Custom classifier '{}': contains {}", spec.name, spec.contains.join(", "));
+        if !spec.excludes.is_empty() {
+            reason.push_str(&format!(" and not {}", spec.excludes.join(", ")));
         }
+        Some(Classification {
+            category: spec.category.clone(),
+            confidence: 1.0, // Declared by the user, not guessed
+            reason,
+            project_type: match spec.category {
+                Category::Project => Some(spec.project_type.clone().unwrap_or_else(|| spec.name.clone())),
+                _ => spec.project_type.clone(),
+            },
+            alternatives: Vec::new(),
+        })
+    }
+
+    fn checks(&self, dir: &DirListing) -> Vec<Check> {
+        let contains = self.spec.contains.iter().map(|m| Check {
+            what: format!("contains {}", m),
+            matched: dir.has_marker(m),
+            confidence: None,
+        });
+        let excludes = self.spec.excludes.iter().map(|m| Check {
+            what: format!("does not contain {}", m),
+            matched: !dir.has_marker(m),
+            confidence: None,
+        });
+        contains.chain(excludes).collect()
+    }
+}
+
+/// What a scan does with a folder classified as a project.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ProjectVerdict {
+    Accepted,    // Confident enough to organize as a project
+    Confirmed,   // Below the threshold, but the user said it is a project
+    NeedsReview, // Below the threshold: listed for review, not moved
+    Rejected,    // The user said it is not a project: scanned as a plain folder
+}
+
+/// Returned by `explain_path`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Explanation {
+    pub path: PathBuf,
+    pub excluded_by: Option<String>, // Why a scan never gets to classify this folder
+    pub classifiers: Vec<ClassifierTrace>,
+    pub result: Classification,
+    pub verdict: Option<ProjectVerdict>, // Only for projects
+}
+
+/// The built-in classifiers plus the config's own, sorted by priority.
+pub struct ClassifierChain {
+    classifiers: Vec<Box<dyn FolderClassifier>>,
+}
+
+impl ClassifierChain {
+    pub fn from_config(config: &Config) -> Self {
+        let mut classifiers: Vec<Box<dyn FolderClassifier>> = vec![
+            Box::new(InstalledSoftwareClassifier),
+            Box::new(AppDataClassifier),
+            Box::new(ProjectMarkerClassifier { markers: config.project_markers.clone() }),
+        ];
+        classifiers.extend(config.custom_classifiers.iter()
+            .filter(|c| c.active)
+            .map(|c| Box::new(ConfigClassifier { spec: c.clone() }) as Box<dyn FolderClassifier>));
+        // Stable: equal priorities keep the order above, custom classifiers in config order.
+        classifiers.sort_by_key(|c| std::cmp::Reverse(c.priority()));
+        ClassifierChain { classifiers }
+    }
+
+    pub fn classify(&self, dir: &DirListing) -> Classification {
+        self.classifiers.iter()
+            .find_map(|c| c.classify(dir))
+            .unwrap_or_default()
+    }
+
+    /// Every classifier's checks and answer for `dir`, in the order they are asked.
+    pub fn trace(&self, dir: &DirListing) -> Vec<ClassifierTrace> {
+        let mut decided = false;
+        self.classifiers.iter()
+            .map(|c| {
+                let outcome = c.classify(dir);
+                let decides = !decided && outcome.is_some();
+                decided |= decides;
+                ClassifierTrace {
+                    classifier: c.name().to_string(),
+                    priority: c.priority(),
+                    checks: c.checks(dir),
+                    outcome,
+                    decided: decides,
+                }
+            })
+            .collect()
     }
+}
+
+// A named heuristic: `Some(reason)` when it fires for the folder.
+type Signal = (&'static str, fn(&DirListing) -> Option<String>);
 
-    // Generic Exe + Dll Check
-    if let Ok(entries) = fs::read_dir(dir) {
+const INSTALLED_SOFTWARE_SIGNALS: &[Signal] = &[
+    ("Windows installer or runtime file", |dir| {
+        let indicators = [
+            "Uninstall.exe", "unins000.exe", "unins001.exe", "setup.exe",
+            "UnityCrashHandler64.exe", "UnityPlayer.dll",
+            "d3dcompiler_47.dll", "opengl32.dll",
+            "steam_api.dll", "steam_api64.dll",
+            "Galaxy64.dll", "tier0.dll",
+            "adb.exe", "AdbWinApi.dll", // Tools
+        ];
+        indicators.into_iter()
+            .find(|i| dir.contains(i))
+            .map(|i| format!("Contains software binary indicator '{}'", i))
+    }),
+    ("Windows .exe with .dll", |dir| {
+        // Generic Exe + Dll Check
         let mut has_exe = false;
         let mut has_dll = false;
-        for entry in entries.flatten().take(50) {
-            let path = entry.path();
-            if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                match ext.to_lowercase().as_str() {
-                    "exe" => has_exe = true,
-                    "dll" => has_dll = true,
-                    _ => {}
-                }
+        for ext in dir.extensions(50) {
+            match ext.as_str() {
+                "exe" => has_exe = true,
+                "dll" => has_dll = true,
+                _ => {}
             }
         }
-        if has_exe && has_dll {
-            return true;
+        (has_exe && has_dll).then(|| "Contains Windows executables with DLLs".to_string())
+    }),
+    ("AppImage", |dir| {
+        dir.entries.iter()
+            .find(|e| !e.is_dir() && e.name.to_lowercase().ends_with(".appimage"))
+            .map(|e| format!("Contains AppImage '{}'", e.name))
+    }),
+    ("Extracted AppImage", |dir| {
+        dir.contains("AppRun").then(|| "Extracted AppImage (AppRun)".to_string())
+    }),
+    ("Snap package", |dir| {
+        (dir.contains("meta") && dir.path.join("meta/snap.yaml").is_file())
+            .then(|| "Snap package (meta/snap.yaml)".to_string())
+    }),
+    ("Flatpak app deployment", |dir| {
+        (dir.contains("metadata") && dir.contains("files") && dir.contains("export"))
+            .then(|| "Flatpak app deployment (metadata, files/, export/)".to_string())
+    }),
+    ("ELF executable with shared libraries", |dir| {
+        // A bare `lib/` is just as common in a C or Go source tree; it has to hold .so files.
+        let has_shared_libs = dir.entries.iter().any(|e| !e.is_dir() && is_shared_library(&e.name))
+            || ["lib", "lib64"].iter().any(|d| dir.contains(d) && holds_shared_library(&dir.path.join(d)));
+        if !has_shared_libs {
+            return None;
         }
-    }
-    
-    false
-}
+        first_elf_executable(dir).map(|elf| format!("ELF executable '{}' with shared libraries (.so)", elf))
+    }),
+    (".desktop launcher with ELF executable", |dir| {
+        let launcher = dir.entries.iter().find(|e| !e.is_dir() && e.name.ends_with(".desktop"))?;
+        first_elf_executable(dir).map(|elf| format!("Launcher '{}' next to ELF executable '{}'", launcher.name, elf))
+    }),
+];
 
-fn is_app_data(dir: &Path) -> bool {
-    // Check for "Data" folders often found in games
-    if let Ok(entries) = fs::read_dir(dir) {
+const APP_DATA_SIGNALS: &[Signal] = &[
+    ("Game data archives", |dir| {
+        // Check for "Data" folders often found in games
         let mut pak_count = 0;
         let mut dat_count = 0;
-        for entry in entries.flatten().take(50) {
-            let path = entry.path();
-            if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                match ext.to_lowercase().as_str() {
-                    "pak" => pak_count += 1,
-                    "vpk" => pak_count += 1,
-                    "dat" => dat_count += 1,
-                    _ => {}
-                }
+        for ext in dir.extensions(50) {
+            match ext.as_str() {
+                "pak" => pak_count += 1,
+                "vpk" => pak_count += 1,
+                "dat" => dat_count += 1,
+                _ => {}
             }
         }
-        if pak_count >= 1 || dat_count >= 5 {
-            return true;
-        }
+        (pak_count >= 1 || dat_count >= 5).then(|| "Contains game data archives (.pak/.vpk/.dat)".to_string())
+    }),
+    ("Steam library", |dir| {
+        dir.contains("steamapps").then(|| "Steam library (steamapps)".to_string())
+    }),
+    ("Steam library metadata", |dir| {
+        (dir.contains("libraryfolders.vdf") || dir.entries.iter().any(|e| e.name.starts_with("appmanifest_") && e.name.ends_with(".acf")))
+            .then(|| "Steam library metadata (appmanifest_*.acf)".to_string())
+    }),
+    ("Wine prefix", |dir| {
+        (dir.contains("drive_c") && (dir.contains("system.reg") || dir.contains("user.reg")))
+            .then(|| "Wine prefix (drive_c, registry files)".to_string())
+    }),
+    ("Flatpak installation", |dir| {
+        (dir.contains("repo") && (dir.contains("app") || dir.contains("runtime")) && dir.path.join("repo/config").is_file())
+            .then(|| "Flatpak installation (repo/, app/, runtime/)".to_string())
+    }),
+    ("Snap user data", |dir| {
+        // ~/snap/<name>: numbered revisions, `current` pointing at one, and `common`
+        (dir.contains("current") && dir.contains("common") && dir.entries.iter().any(|e| e.is_dir() && e.name.parse::<u32>().is_ok()))
+            .then(|| "Snap user data (current, common, revisions)".to_string())
+    }),
+    ("Launcher folder", |dir| {
+        let launchers = dir.entries.iter().filter(|e| !e.is_dir() && e.name.ends_with(".desktop")).count();
+        (launchers > 0 && launchers == dir.entries.iter().filter(|e| !e.is_dir()).count())
+            .then(|| "Only contains .desktop launchers".to_string())
+    }),
+];
+
+/// First signal that fires, in table order.
+fn first_signal(signals: &[Signal], dir: &DirListing) -> Option<String> {
+    signals.iter().find_map(|(_, check)| check(dir))
+}
+
+/// Every signal, fired or not.
+fn signal_checks(signals: &[Signal], dir: &DirListing, confidence: f32) -> Vec<Check> {
+    signals.iter()
+        .map(|(name, check)| {
+            let reason = check(dir);
+            Check {
+                what: reason.clone().unwrap_or_else(|| name.to_string()),
+                matched: reason.is_some(),
+                confidence: Some(confidence),
+            }
+        })
+        .collect()
+}
+
+// `libfoo.so`, `libfoo.so.1`, `libfoo.so.1.2.3`
+fn is_shared_library(name: &str) -> bool {
+    name.ends_with(".so") || name.contains(".so.")
+}
+
+// Checks the first 50 entries, like the other signals.
+fn holds_shared_library(dir: &Path) -> bool {
+    std::fs::read_dir(dir).map(|entries| {
+        entries.flatten()
+            .take(50)
+            .any(|e| e.file_type().is_ok_and(|t| t.is_file()) && is_shared_library(&e.file_name().to_string_lossy()))
+    }).unwrap_or(false)
+}
+
+/// Name of the first regular file (of the first 50 entries) with an ELF header
+/// that is not a shared library.
+fn first_elf_executable(dir: &DirListing) -> Option<String> {
+    dir.entries.iter()
+        .take(50)
+        .filter(|e| e.kind == EntryKind::File && !is_shared_library(&e.name))
+        .find(|e| has_elf_magic(&e.path))
+        .map(|e| e.name.clone())
+}
+
+fn has_elf_magic(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    File::open(path).and_then(|mut f| f.read_exact(&mut magic)).is_ok() && &magic == b"\x7FELF"
+}
+
+// Frameworks, most specific first: a Tauri app usually has a Vite frontend too.
+const FRAMEWORK_MARKERS: &[&str] = &[
+    "src-tauri/tauri.conf.json", "src-tauri/tauri.conf.json5",
+    "next.config.js", "next.config.mjs", "remix.config.js", "nest-cli.json",
+    "angular.json", "vue.config.js", "ionic.config.json", "pubspec.yaml",
+    "manage.py", "artisan", "ProjectSettings", "steam_settings",
+    "vite.config.js", "vite.config.ts",
+];
+
+const VCS_MARKERS: &[&str] = &[".git", ".hg", ".svn", ".vscode", ".idea"];
+
+fn marker_kind(marker: &str) -> MarkerKind {
+    if FRAMEWORK_MARKERS.contains(&marker) {
+        MarkerKind::Framework
+    } else if VCS_MARKERS.contains(&marker) {
+        MarkerKind::Vcs
+    } else {
+        MarkerKind::Language
     }
-    false
 }
 
-fn detect_project(dir: &Path, markers: &[String]) -> Option<(String, f32)> {
-    // Returns (ProjectType, Confidence)
-    
+/// Every marker `detect_project` looks for, each once, in the order it looks,
+/// with the confidence a match would give.
+fn project_marker_candidates(markers: &[String]) -> Vec<TypeCandidate> {
     // 1. Strong Markers (Confidence 1.0)
     // These define a project root clearly.
     let strong_markers = [
@@ -138,36 +452,77 @@ fn detect_project(dir: &Path, markers: &[String]) -> Option<(String, f32)> {
         "lines", "CMakeLists.txt", "Makefile", ".git", ".hg", ".svn", 
         ".vscode", ".idea", "*.sln", "*.csproj", "*.xcodeproj"
     ];
-
-    for marker in strong_markers {
-        // Handle glob markers in strong list
-        if marker.starts_with('*') {
-             // simplified glob check for strong markers if needed
-             if crate::projects::search_for_marker(dir, marker) {
-                 return Some((marker.to_string(), 1.0));
-             }
-        } else {
-             if dir.join(marker).exists() {
-                 return Some((marker.to_string(), 1.0));
-             }
-        }
-    }
+    let mut candidates: Vec<(String, f32)> = strong_markers.iter().map(|m| (m.to_string(), 1.0)).collect();
 
     // 2. Config Provided Markers (Variable Confidence)
     // The config contains both strong and weak markers. We need to distinguish.
     for marker in markers {
-         if crate::projects::search_for_marker(dir, marker) {
-             // Is it a weak marker?
-             if marker.starts_with('*') || marker.ends_with(".js") || marker.ends_with(".py") || marker.ends_with(".ts") {
-                 // Weak marker found.
-                 // Confirm it's not just a single script in a random folder?
-                 // For now, give it lower confidence.
-                 return Some((marker.clone(), 0.6));
-             } else {
-                 return Some((marker.clone(), 1.0));
-             }
-         }
-    }
-
-    None
+        // Is it a weak marker? A lone script in a random folder is not much of a project.
+        // Framework config files (`manage.py`, `vite.config.ts`) are scripts too, but name a project on their own.
+        let weak = !FRAMEWORK_MARKERS.contains(&marker.as_str())
+            && (marker.starts_with('*') || marker.ends_with(".js") || marker.ends_with(".py") || marker.ends_with(".ts"));
+        candidates.push((marker.clone(), if weak { 0.6 } else { 1.0 }));
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    candidates.into_iter()
+        .filter(|(marker, _)| seen.insert(marker.clone()))
+        .map(|(marker, confidence)| TypeCandidate { kind: marker_kind(&marker), marker, confidence })
+        .collect()
+}
+
+/// Every marker present in `dir`, best first: by kind, then the more specific
+/// framework, then confidence, then the order they are looked for.
+fn detect_project(dir: &DirListing, markers: &[String]) -> Vec<TypeCandidate> {
+    let mut found: Vec<TypeCandidate> = project_marker_candidates(markers).into_iter()
+        .filter(|c| dir.has_marker(&c.marker))
+        .collect();
+    let specificity = |c: &TypeCandidate| FRAMEWORK_MARKERS.iter().position(|m| *m == c.marker).unwrap_or(0);
+    found.sort_by(|a, b| {
+        b.kind.cmp(&a.kind)
+            .then(specificity(a).cmp(&specificity(b)))
+            .then(b.confidence.total_cmp(&a.confidence))
+    });
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::default_config;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn classify(dir: &Path) -> Classification {
+        ClassifierChain::from_config(&default_config()).classify(&DirListing::read(dir))
+    }
+
+    // Enough of an ELF header for `has_elf_magic`.
+    fn write_elf(path: &Path) {
+        fs::write(path, b"\x7FELF\x02\x01\x01\0").unwrap();
+    }
+
+    #[test]
+    fn a_built_c_project_with_lib_sources_is_a_project() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("Makefile"), "all:\n\tcc -o app main.c lib/util.c\n").unwrap();
+        fs::create_dir(tmp.path().join("lib")).unwrap();
+        fs::write(tmp.path().join("lib/util.c"), "int util(void) { return 1; }\n").unwrap();
+        write_elf(&tmp.path().join("app"));
+
+        let result = classify(tmp.path());
+        assert_eq!(result.category, Category::Project);
+        assert_eq!(result.project_type.as_deref(), Some("Makefile"));
+    }
+
+    #[test]
+    fn an_executable_with_shared_objects_in_lib_is_installed_software() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("Makefile"), "").unwrap();
+        fs::create_dir(tmp.path().join("lib")).unwrap();
+        write_elf(&tmp.path().join("lib/libapp.so.1"));
+        write_elf(&tmp.path().join("app"));
+
+        assert_eq!(classify(tmp.path()).category, Category::InstalledProgram);
+    }
 }
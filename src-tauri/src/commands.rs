@@ -1,13 +1,21 @@
-use crate::models::{Config, FileItem, Project, Plan, FileOperation};
+use crate::models::{Config, FileItem, Project, Plan, HistoryEntry, IncompleteBatch, RecoveryAction, DedupMode, ScanResult};
 use crate::scanner::Scanner;
+use crate::classifier::Explanation;
 use crate::planner::generate_plan;
-use crate::executor::{execute_plan, undo_single_op};
-use tauri::{State, Manager};
-use std::sync::Mutex;
+use crate::executor::undo_batch;
+use crate::journal::Journal;
+use crate::wal::{apply_logged, recover, WriteAheadLog};
+use tauri::{Emitter, State, Manager};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::path::PathBuf;
 
+pub const SCAN_PROGRESS_EVENT: &str = "scan-progress";
+
 pub struct AppState {
-    pub undo_stack: Mutex<Vec<Vec<FileOperation>>>,
+    pub journal: Mutex<Journal>, // Also serializes applies, undos and recoveries
+    pub wal: WriteAheadLog,
+    pub scan_cancel: Mutex<Arc<AtomicBool>>, // Flag of the most recent scan
 }
 
 #[tauri::command]
@@ -15,32 +23,61 @@ pub fn get_default_config() -> Config {
     crate::config::default_config()
 }
 
+/// Walks on a blocking thread and emits `scan-progress` events while it runs.
+#[tauri::command]
+pub async fn scan_directory(app: tauri::AppHandle, state: State<'_, AppState>, path: String, config: Config) -> Result<ScanResult, String> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    *state.scan_cancel.lock().unwrap() = cancel.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut scanner = Scanner::new(config)
+            .with_cancel(cancel)
+            .with_progress(move |progress| {
+                let _ = app.emit(SCAN_PROGRESS_EVENT, progress);
+            });
+        scanner.scan(&PathBuf::from(path));
+        scanner.get_results()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Stops the running scan; `scan_directory` then returns what it found so far.
 #[tauri::command]
-pub fn scan_directory(path: String, config: Config) -> (Vec<FileItem>, Vec<Project>) {
-    let mut scanner = Scanner::new(config);
-    scanner.scan(&PathBuf::from(path));
-    scanner.get_results()
+pub fn cancel_scan(state: State<AppState>) {
+    state.scan_cancel.lock().unwrap().store(true, Ordering::Relaxed);
 }
 
+/// Why a folder is or is not classified as a project when scanning `root`.
 #[tauri::command]
-pub fn create_plan(files: Vec<FileItem>, projects: Vec<Project>, config: Config, root: String) -> Plan {
+pub fn explain_path(path: String, root: String, config: Config) -> Explanation {
+    Scanner::new(config).explain(&PathBuf::from(root), &PathBuf::from(path))
+}
+
+#[tauri::command]
+pub fn create_plan(files: Vec<FileItem>, projects: Vec<Project>, config: Config, root: String) -> Result<Plan, String> {
     generate_plan(&files, &projects, &config, &PathBuf::from(&root))
 }
 
+#[tauri::command]
+pub fn create_dedup_plan(files: Vec<FileItem>, root: String, mode: DedupMode) -> Plan {
+    crate::duplicates::generate_dedup_plan(&files, &PathBuf::from(&root), mode)
+}
+
 #[tauri::command]
 pub fn apply_plan(plan: Plan, state: State<AppState>) -> Result<String, String> {
-    let executed = execute_plan(&plan)?;
-    state.undo_stack.lock().unwrap().push(executed);
+    let journal = state.journal.lock().unwrap();
+    apply_logged(&state.wal, &journal, &plan)?;
     Ok("Plan applied successfully".to_string())
 }
 
 #[tauri::command]
 pub fn undo_last_operation(state: State<AppState>) -> Result<String, String> {
-    let mut stack = state.undo_stack.lock().unwrap();
-    if let Some(ops) = stack.pop() {
-        for op in ops.iter().rev() {
-            undo_single_op(op).map_err(|e| format!("Undo error: {}", e))?;
-        }
+    let journal = state.journal.lock().unwrap();
+    if let Some(entry) = journal.last()? {
+        undo_batch(&entry.operations)?;
+        // Only forget the batch once it is fully reverted, so a failed undo can be retried.
+        journal.remove(&entry.id)?;
         Ok("Undo successful".to_string())
     } else {
         Err("Nothing to undo".to_string())
@@ -51,25 +88,14 @@ pub fn undo_last_operation(state: State<AppState>) -> Result<String, String> {
 pub fn load_config_file(app: tauri::AppHandle) -> Result<Config, String> {
     let config_dir = app.path().app_config_dir()
         .map_err(|e| e.to_string())?;
-    let path = config_dir.join("config.json");
-    
-    if path.exists() {
-        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&content).map_err(|e| e.to_string())
-    } else {
-        Ok(crate::config::default_config())
-    }
+    crate::config::load_config(&config_dir)
 }
 
 #[tauri::command]
 pub fn save_config_file(app: tauri::AppHandle, config: Config) -> Result<(), String> {
     let config_dir = app.path().app_config_dir()
         .map_err(|e| e.to_string())?;
-    std::fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
-    
-    let path = config_dir.join("config.json");
-    let content = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
-    std::fs::write(path, content).map_err(|e| e.to_string())
+    crate::config::save_config(&config_dir, &config)
 }
 
 #[tauri::command]
@@ -77,9 +103,20 @@ pub async fn suggest_project_name(project: Project, api_key: String) -> Result<S
     crate::ai::suggest_name(&project, &api_key).await.ok_or_else(|| "Failed to generate name".to_string())
 }
 #[tauri::command]
-pub fn get_history(state: State<AppState>) -> Vec<Vec<FileOperation>> {
-    let stack = state.undo_stack.lock().unwrap();
-    stack.clone()
+pub fn get_history(state: State<AppState>) -> Result<Vec<HistoryEntry>, String> {
+    state.journal.lock().unwrap().entries()
+}
+
+#[tauri::command]
+pub fn get_incomplete_batches(state: State<AppState>) -> Result<Vec<IncompleteBatch>, String> {
+    let _journal = state.journal.lock().unwrap();
+    state.wal.incomplete_batches()
+}
+
+#[tauri::command]
+pub fn recover_incomplete_batch(batch_id: String, action: RecoveryAction, state: State<AppState>) -> Result<String, String> {
+    let journal = state.journal.lock().unwrap();
+    recover(&state.wal, &journal, &batch_id, action)
 }
 
 #[tauri::command]
@@ -1,4 +1,112 @@
-use crate::models::{Config, Rule};
+use crate::models::{default_min_project_confidence, Config, ConflictStrategy, CustomClassifier, ProjectDestination, Rule, RuleAction, RuleConditions, SymlinkMode};
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE_NAME: &str = "config.json";
+
+// Must match "identifier" in tauri.conf.json so the CLI and the app share a config dir.
+const APP_IDENTIFIER: &str = "com.deskorganizer.app";
+
+/// Same location Tauri resolves for `app_config_dir()`, usable without an AppHandle.
+pub fn default_config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join(APP_IDENTIFIER))
+}
+
+/// Reject configs the planner could not apply as written, e.g. an unknown
+/// `{placeholder}` in a destination or a regex that does not compile.
+pub fn validate_config(config: &Config) -> Result<(), String> {
+    let mut errors: Vec<String> = config.rules.iter()
+        .filter_map(|rule| crate::rules::CompiledRule::new(rule).err())
+        .collect();
+    errors.extend(config.custom_classifiers.iter().filter_map(validate_classifier));
+    errors.extend(config.project_destinations.iter().flat_map(|d| &d.markers).filter_map(|m| {
+        glob::Pattern::new(m).err().map(|e| format!("Project destination: invalid marker pattern '{}': {}", m, e))
+    }));
+    if !(0.0..=1.0).contains(&config.min_project_confidence) {
+        errors.push(format!("min_project_confidence must be between 0 and 1, not {}", config.min_project_confidence));
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+fn validate_classifier(classifier: &CustomClassifier) -> Option<String> {
+    if classifier.contains.is_empty() {
+        // Would match every folder.
+        return Some(format!("Classifier '{}': `contains` must list at least one entry", classifier.name));
+    }
+    classifier.contains.iter().chain(&classifier.excludes)
+        .find_map(|m| glob::Pattern::new(m).err().map(|e| format!("Classifier '{}': invalid pattern '{}': {}", classifier.name, m, e)))
+}
+
+/// Where each kind of project goes. The last entry catches everything else.
+pub fn default_project_destinations() -> Vec<ProjectDestination> {
+    let table: &[(&[&str], &str)] = &[
+        // Frameworks
+        (&["next.config.js", "next.config.mjs"], "Projects/NextJS"),
+        (&["remix.config.js"], "Projects/Remix"),
+        (&["angular.json"], "Projects/Angular"),
+        (&["vue.config.js"], "Projects/Vue"),
+        (&["vite.config.js", "vite.config.ts"], "Projects/Vite"),
+        (&["nest-cli.json"], "Projects/NestJS"),
+        (&["manage.py"], "Projects/Django"),
+        (&["src-tauri/tauri.conf.json", "src-tauri/tauri.conf.json5"], "Projects/Tauri"),
+
+        // General Languages
+        (&["package.json", "node_modules", "*.js", "*.ts"], "Projects/Node"),
+        (&["Cargo.toml"], "Projects/Rust"),
+        (&["pyproject.toml", "requirements.txt", "venv", "app.py", "main.py", "*.py"], "Projects/Python"),
+        (&["index.html"], "Projects/Web"),
+        (&[".obsidian"], "Documents/Vaults"), // Safer to keep vaults in Docs
+        (&["go.mod"], "Projects/Go"),
+        (&["pom.xml", "build.gradle"], "Projects/Java"),
+        (&["*.sln"], "Projects/DotNet"),
+        (&[".git"], "Projects/Git"),
+        (&["Makefile", "CMakeLists.txt"], "Projects/Cpp"),
+        (&["steam_settings", "*.exe", "Assets", "ProjectSettings"], "Games"),
+        (&["composer.json", "index.php", "artisan"], "Projects/PHP"),
+        (&["Gemfile"], "Projects/Ruby"),
+        (&["pubspec.yaml"], "Projects/Flutter"),
+        (&["AndroidManifest.xml", "build.gradle.kts", "*.xcodeproj", "*.xcworkspace", "Package.swift", "ionic.config.json"], "Projects/Mobile"),
+        (&["Dockerfile", "docker-compose.yml", "Containerfile", "*.tf"], "Projects/DevOps"),
+        (&["*.Rproj"], "Projects/Data"),
+        (&["*.ipynb"], "Projects/Jupyter"),
+        (&["mix.exs"], "Projects/Elixir"),
+        (&["Project.toml"], "Projects/Julia"),
+        (&["*"], "Projects/Other"), // Fallback
+    ];
+    table.iter()
+        .map(|(markers, destination)| ProjectDestination {
+            markers: markers.iter().map(|m| m.to_string()).collect(),
+            destination: destination.to_string(),
+        })
+        .collect()
+}
+
+/// How `confirmed_projects` and `rejected_projects` entries are stored and compared,
+/// so `scripts`, `./scripts` and `/home/me/scripts` are the same folder.
+pub fn review_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+pub fn load_config(config_dir: &Path) -> Result<Config, String> {
+    let path = config_dir.join(CONFIG_FILE_NAME);
+    if path.exists() {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(default_config())
+    }
+}
+
+pub fn save_config(config_dir: &Path, config: &Config) -> Result<(), String> {
+    validate_config(config)?;
+    std::fs::create_dir_all(config_dir).map_err(|e| e.to_string())?;
+    let path = config_dir.join(CONFIG_FILE_NAME);
+    let content = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    std::fs::write(path, content).map_err(|e| e.to_string())
+}
 
 pub fn default_config() -> Config {
     Config {
@@ -97,48 +205,116 @@ pub fn default_config() -> Config {
             // Jupyter
             "*.ipynb".to_string(), 
         ],
+        trash_empty_folders: false,
+        detect_mime: true,
+        conflict_strategy: ConflictStrategy::Rename,
+        respect_ignore_files: false,
+        loose_file_depth: 0,
+        symlink_mode: SymlinkMode::Skip,
+        custom_classifiers: Vec::new(),
+        min_project_confidence: default_min_project_confidence(),
+        confirmed_projects: Vec::new(),
+        rejected_projects: Vec::new(),
+        project_destinations: default_project_destinations(),
+        refuse_dirty_repos: false,
+        refuse_linked_repos: false,
+        collect_project_metadata: true,
         rules: vec![
             Rule {
                 name: "Images".to_string(),
                 patterns: vec!["*.jpg".to_string(), "*.jpeg".to_string(), "*.png".to_string(), "*.gif".to_string(), "*.svg".to_string(), "*.webp".to_string(), "*.bmp".to_string(), "*.tiff".to_string()],
                 destination: "Media/Images".to_string(),
                 active: true,
+                action: RuleAction::Move,
+                name_regex: None,
+                mime_types: vec!["image/*".to_string()],
+                conditions: RuleConditions::default(),
+                conflict_strategy: None,
+                max_depth: None,
             },
             Rule {
                 name: "Videos".to_string(),
                 patterns: vec!["*.mp4".to_string(), "*.mkv".to_string(), "*.mov".to_string(), "*.avi".to_string(), "*.webm".to_string()],
                 destination: "Media/Videos".to_string(),
                 active: true,
+                action: RuleAction::Move,
+                name_regex: None,
+                mime_types: vec!["video/*".to_string()],
+                conditions: RuleConditions::default(),
+                conflict_strategy: None,
+                max_depth: None,
             },
             Rule {
                 name: "Audio".to_string(),
                 patterns: vec!["*.mp3".to_string(), "*.wav".to_string(), "*.flac".to_string(), "*.aac".to_string(), "*.ogg".to_string()],
                 destination: "Media/Audio".to_string(),
                 active: true,
+                action: RuleAction::Move,
+                name_regex: None,
+                mime_types: vec!["audio/*".to_string()],
+                conditions: RuleConditions::default(),
+                conflict_strategy: None,
+                max_depth: None,
             },
             Rule {
                 name: "Documents".to_string(),
                 patterns: vec!["*.pdf".to_string(), "*.docx".to_string(), "*.doc".to_string(), "*.txt".to_string(), "*.xlsx".to_string(), "*.pptx".to_string(), "*.csv".to_string(), "*.md".to_string()],
                 destination: "Documents".to_string(),
                 active: true,
+                action: RuleAction::Move,
+                name_regex: None,
+                mime_types: vec!["application/pdf".to_string(), "application/rtf".to_string(), "application/vnd.openxmlformats-officedocument.*".to_string(), "application/vnd.oasis.opendocument.*".to_string()],
+                conditions: RuleConditions::default(),
+                conflict_strategy: None,
+                max_depth: None,
             },
             Rule {
                 name: "Installers".to_string(),
                 patterns: vec!["*.exe".to_string(), "*.msi".to_string()],
                 destination: "Downloads/Installers".to_string(),
                 active: true,
+                action: RuleAction::Move,
+                name_regex: None,
+                mime_types: Vec::new(),
+                conditions: RuleConditions::default(),
+                conflict_strategy: None,
+                max_depth: None,
             },
             Rule {
                 name: "Archives".to_string(),
                 patterns: vec!["*.zip".to_string(), "*.rar".to_string(), "*.7z".to_string(), "*.tar.gz".to_string()],
                 destination: "Downloads/Archives".to_string(),
                 active: true,
+                action: RuleAction::Move,
+                name_regex: None,
+                mime_types: vec!["application/zip".to_string(), "application/vnd.rar".to_string(), "application/x-7z-compressed".to_string(), "application/gzip".to_string(), "application/x-bzip2".to_string(), "application/x-xz".to_string()],
+                conditions: RuleConditions::default(),
+                conflict_strategy: None,
+                max_depth: None,
             },
             Rule {
                 name: "Shortcuts".to_string(),
                 patterns: vec!["*.lnk".to_string(), "*.url".to_string()],
                 destination: "Shortcuts".to_string(),
                 active: true,
+                action: RuleAction::Move,
+                name_regex: None,
+                mime_types: Vec::new(),
+                conditions: RuleConditions::default(),
+                conflict_strategy: None,
+                max_depth: None,
+            },
+            Rule {
+                name: "Junk".to_string(),
+                patterns: vec!["*.tmp".to_string(), "*.crdownload".to_string(), "*.part".to_string(), "~$*".to_string()],
+                destination: String::new(), // Not used for Trash
+                active: false, // Opt-in
+                action: RuleAction::Trash,
+                name_regex: None,
+                mime_types: Vec::new(),
+                conditions: RuleConditions::default(),
+                conflict_strategy: None,
+                max_depth: None,
             },
         ],
     }
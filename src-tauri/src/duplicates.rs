@@ -0,0 +1,155 @@
+use crate::models::{DedupMode, FileItem, FileOperation, OperationType, Plan, RuleAction};
+use crate::listing::file_id;
+use crate::planner::park;
+use crate::trash::home_trash_dir;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use uuid::Uuid;
+
+// Bytes hashed from each end of a file for the quick pass.
+const SAMPLE_LEN: u64 = 16 * 1024;
+
+pub struct DuplicateGroup<'a> {
+    pub hash: String, // Full SHA-256, hex
+    pub size: u64,
+    pub files: Vec<&'a FileItem>, // Canonical copy first
+}
+
+/// Files with identical contents, found in three passes that each only look at
+/// the survivors of the previous one: same size, same quick hash (start and end
+/// of the file), same full hash.
+pub fn find_duplicates(files: &[FileItem]) -> Vec<DuplicateGroup<'_>> {
+    let mut by_size: BTreeMap<u64, Vec<&FileItem>> = BTreeMap::new();
+    // Symlinks would be hashed through to their targets.
+    for file in files.iter().filter(|f| !f.is_dir && !f.is_symlink && f.size > 0) {
+        by_size.entry(file.size).or_default().push(file);
+    }
+
+    let mut groups = Vec::new();
+    for (size, same_size) in by_size.into_iter().filter(|(_, v)| v.len() > 1) {
+        for same_quick in group_by(same_size, |f| quick_hash(&f.path, size)) {
+            for (hash, mut same_full) in group_by_keyed(same_quick, |f| full_hash(&f.path)) {
+                same_full.sort_by(|a, b| canonical_order(a, b));
+                // Names that are already hard links to one file take no extra space.
+                let mut seen = HashSet::new();
+                same_full.retain(|f| match file_id(&f.path) {
+                    Some(id) => seen.insert(id),
+                    None => true,
+                });
+                if same_full.len() > 1 {
+                    groups.push(DuplicateGroup { hash, size, files: same_full });
+                }
+            }
+        }
+    }
+    groups
+}
+
+fn group_by(files: Vec<&FileItem>, key: impl Fn(&FileItem) -> io::Result<String>) -> Vec<Vec<&FileItem>> {
+    group_by_keyed(files, key).into_iter().map(|(_, v)| v).collect()
+}
+
+// Unreadable files are left out rather than guessed at.
+fn group_by_keyed(files: Vec<&FileItem>, key: impl Fn(&FileItem) -> io::Result<String>) -> Vec<(String, Vec<&FileItem>)> {
+    let mut buckets: BTreeMap<String, Vec<&FileItem>> = BTreeMap::new();
+    for file in files {
+        if let Ok(k) = key(file) {
+            buckets.entry(k).or_default().push(file);
+        }
+    }
+    buckets.into_iter().filter(|(_, v)| v.len() > 1).collect()
+}
+
+/// Which copy to keep: one without a copy marker in its name ("report (1).pdf",
+/// "Copy of report.pdf"), then the oldest, then the shortest name.
+fn canonical_order(a: &FileItem, b: &FileItem) -> std::cmp::Ordering {
+    looks_like_copy(&a.name).cmp(&looks_like_copy(&b.name))
+        .then(a.modified.cmp(&b.modified))
+        .then(a.name.len().cmp(&b.name.len()))
+        .then(a.path.cmp(&b.path))
+}
+
+fn looks_like_copy(name: &str) -> bool {
+    lazy_static::lazy_static! {
+        static ref COPY_MARKER: regex::Regex =
+            regex::Regex::new(r"(?i)(\(\d+\)|\bcopy\b)").unwrap();
+    }
+    COPY_MARKER.is_match(name)
+}
+
+pub fn quick_hash(path: &Path, size: u64) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; SAMPLE_LEN as usize];
+
+    let n = read_up_to(&mut file, &mut buf)?;
+    hasher.update(&buf[..n]);
+    if size > 2 * SAMPLE_LEN {
+        file.seek(SeekFrom::End(-(SAMPLE_LEN as i64)))?;
+        let n = read_up_to(&mut file, &mut buf)?;
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+pub fn full_hash(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hex(&hasher.finalize()))
+}
+
+fn read_up_to(file: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Keep the canonical copy of each duplicate group and trash (or hard-link) the rest.
+pub fn generate_dedup_plan(files: &[FileItem], root: &Path, mode: DedupMode) -> Plan {
+    let mut operations = Vec::new();
+    let mut intended_paths = HashSet::new();
+    let trash_dir = home_trash_dir();
+    let groups = find_duplicates(files);
+
+    for group in &groups {
+        let keep = group.files[0];
+        let reason = format!(
+            "Duplicate of {} ({} identical files, {} bytes, sha256 {})",
+            keep.name, group.files.len(), group.size, &group.hash[..12],
+        );
+        for dup in &group.files[1..] {
+            // Hard-linking first parks the duplicate, so undo can bring the original file back.
+            operations.push(park(&dup.path, root, trash_dir.as_deref(), &mut intended_paths, RuleAction::Trash, reason.clone()));
+            if mode == DedupMode::HardLink {
+                operations.push(FileOperation {
+                    id: Uuid::new_v4().to_string(),
+                    op_type: OperationType::HardLink,
+                    source: Some(keep.path.clone()),
+                    destination: dup.path.clone(),
+                    reason: reason.clone(),
+                    warnings: Vec::new(),
+                });
+            }
+        }
+    }
+
+    let wasted: u64 = groups.iter().map(|g| g.size * (g.files.len() as u64 - 1)).sum();
+    Plan {
+        summary: format!("{} duplicate groups, {} bytes reclaimable ({} operations)", groups.len(), wasted, operations.len()),
+        operations,
+        root: Some(root.to_path_buf()),
+    }
+}
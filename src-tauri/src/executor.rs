@@ -1,15 +1,29 @@
 use crate::models::{FileOperation, OperationType, Plan};
+use crate::transfer::{copy_path, move_path, remove_tree, verify_remainder};
+use crate::trash;
+use crate::wal::WalBatch;
 use std::fs;
+use std::path::Path;
 
-pub fn execute_plan(plan: &Plan) -> Result<Vec<FileOperation>, String> {
+/// Deleted items are parked here (under the scan root) instead of being unlinked,
+/// so a Delete can be undone. The planner puts each item in its own subfolder.
+pub const DELETED_DIR_NAME: &str = ".organizer-deleted";
+
+/// Apply every operation in order, rolling back on the first failure.
+/// With a `wal`, each operation is logged before it runs and confirmed after.
+pub fn execute_plan(plan: &Plan, mut wal: Option<&mut WalBatch>) -> Result<Vec<FileOperation>, String> {
     let mut executed = Vec::new();
     
     for op in &plan.operations {
-        if let Err(e) = execute_single_op(op) {
+        if let Err(e) = execute_logged(op, wal.as_deref_mut()) {
             // Rollback!
             // We must undo all `executed` ops in reverse order
             for done_op in executed.iter().rev() {
-                let _ = undo_single_op(done_op);
+                if undo_single_op(done_op).is_ok() {
+                    if let Some(wal) = wal.as_deref_mut() {
+                        let _ = wal.undone(&done_op.id);
+                    }
+                }
             }
             return Err(format!("Failed at {}: {}. Rolled back.", op.destination.display(), e));
         }
@@ -19,43 +33,180 @@ pub fn execute_plan(plan: &Plan) -> Result<Vec<FileOperation>, String> {
     Ok(executed)
 }
 
+fn execute_logged(op: &FileOperation, wal: Option<&mut WalBatch>) -> Result<(), String> {
+    let Some(wal) = wal else {
+        return execute_single_op(op);
+    };
+    wal.intent(op)?;
+    let dest_was_free = op.destination.symlink_metadata().is_err();
+    match execute_single_op(op) {
+        Ok(()) => wal.done(&op.id).inspect_err(|_| {
+            // Could not confirm it, so don't leave it applied either.
+            let _ = undo_single_op(op);
+        }),
+        Err(e) if dest_was_free && is_half_moved(op) => {
+            // The copy is in place but the source is partly gone: neither undoing nor
+            // retrying is safe here, so the op stays in flight for `recover`.
+            Err(format!("{} (moved, but the source could not be fully removed; recover the batch to finish)", e))
+        }
+        Err(e) => {
+            let _ = wal.failed(&op.id);
+            Err(e)
+        }
+    }
+}
+
+// Both existing after a failed move onto a free destination means a cross-device
+// move stopped while removing the source.
+fn is_half_moved(op: &FileOperation) -> bool {
+    matches!(op.op_type, OperationType::Move | OperationType::Delete | OperationType::Trash)
+        && op.source.as_ref().is_some_and(|src| src.symlink_metadata().is_ok())
+        && op.destination.symlink_metadata().is_ok()
+}
+
+/// Settle the operation that was in flight when an apply was interrupted: finish
+/// it if only cleanup was left, or undo its first steps so it can simply run again.
+/// Returns whether it is now in effect.
+pub fn settle_interrupted(op: &FileOperation) -> Result<bool, String> {
+    let dest_exists = op.destination.symlink_metadata().is_ok();
+    match op.op_type {
+        OperationType::Move | OperationType::Delete | OperationType::Trash => {
+            let Some(src) = op.source.as_ref().filter(|src| src.symlink_metadata().is_ok()) else {
+                return Ok(dest_exists);
+            };
+            // A cross-device move only renames the finished copy into place once it has
+            // been verified, then removes the source. Killed in between, both exist.
+            if dest_exists && verify_remainder(src, &op.destination).is_ok() {
+                remove_tree(src).map_err(|e| e.to_string())?;
+                return Ok(true);
+            }
+            if op.op_type == OperationType::Trash && !dest_exists {
+                // The info file is written first and would block the retry.
+                trash::remove_info(&op.destination);
+            }
+            Ok(false)
+        },
+        OperationType::Copy | OperationType::HardLink => Ok(dest_exists),
+        OperationType::CreateDir => Ok(op.destination.is_dir()),
+    }
+}
+
+/// Reverse a batch previously returned by `execute_plan`, last operation first.
+pub fn undo_batch(ops: &[FileOperation]) -> Result<(), String> {
+    for op in ops.iter().rev() {
+        undo_single_op(op).map_err(|e| format!("Undo error: {}", e))?;
+    }
+    Ok(())
+}
+
 fn execute_single_op(op: &FileOperation) -> Result<(), String> {
     match op.op_type {
-        OperationType::Move => {
+        // A Delete is a move into the deleted-items area; see DELETED_DIR_NAME.
+        OperationType::Move | OperationType::Delete => {
             if let Some(src) = &op.source {
-                if let Some(parent) = op.destination.parent() {
-                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-                }
-                fs::rename(src, &op.destination).map_err(|e| e.to_string())?;
+                ensure_free(&op.destination)?;
+                create_parent(&op.destination)?;
+                move_path(src, &op.destination).map_err(|e| e.to_string())?;
+            }
+        },
+        OperationType::Copy => {
+            if let Some(src) = &op.source {
+                ensure_free(&op.destination)?;
+                create_parent(&op.destination)?;
+                copy_path(src, &op.destination).map_err(|e| e.to_string())?;
             }
         },
         OperationType::CreateDir => {
             fs::create_dir_all(&op.destination).map_err(|e| e.to_string())?;
         },
-        _ => {}
+        OperationType::HardLink => {
+            if let Some(src) = &op.source {
+                create_parent(&op.destination)?;
+                fs::hard_link(src, &op.destination).map_err(|e| e.to_string())?;
+            }
+        },
+        OperationType::Trash => {
+            if let Some(src) = &op.source {
+                trash::write_info(src, &op.destination).map_err(|e| e.to_string())?;
+                if let Err(e) = move_path(src, &op.destination) {
+                    // A half-finished move still needs its info file.
+                    if op.destination.symlink_metadata().is_err() {
+                        trash::remove_info(&op.destination);
+                    }
+                    return Err(e.to_string());
+                }
+            }
+        },
     }
     Ok(())
 }
 
 pub fn undo_single_op(op: &FileOperation) -> Result<(), String> {
     match op.op_type {
-        OperationType::Move => {
-            if let Some(src) = &op.source {
-               // Inverse: Move Destination back to Source
-               if op.destination.exists() {
-                   // Ensure parent of source exists (it should, unless we deleted it?)
-                   if let Some(parent) = src.parent() {
-                        let _ = fs::create_dir_all(parent);
-                   }
-                   fs::rename(&op.destination, src).map_err(|e| e.to_string())?;
-               }
+        OperationType::Move => move_back(op)?,
+        OperationType::Delete => {
+            move_back(op)?;
+            // Drop the per-item holder folder, and the deleted-items area once it is empty.
+            if let Some(holder) = op.destination.parent() {
+                if fs::remove_dir(holder).is_ok() {
+                    if let Some(area) = holder.parent() {
+                        let _ = fs::remove_dir(area);
+                    }
+                }
+            }
+        },
+        OperationType::Trash => {
+            move_back(op)?;
+            if op.destination.symlink_metadata().is_err() {
+                trash::remove_info(&op.destination);
+            }
+        },
+        OperationType::Copy => {
+            // Inverse: Remove the copy, the original was never touched
+            if op.destination.symlink_metadata().is_ok() {
+                remove_tree(&op.destination).map_err(|e| e.to_string())?;
+            }
+        },
+        OperationType::HardLink => {
+            // Inverse: Remove the link; the file it pointed to stays at source
+            if op.destination.symlink_metadata().is_ok() {
+                fs::remove_file(&op.destination).map_err(|e| e.to_string())?;
             }
         },
         OperationType::CreateDir => {
              // Inverse: Delete dir if empty
              let _ = fs::remove_dir(&op.destination);
         },
-        _ => {}
+    }
+    Ok(())
+}
+
+fn move_back(op: &FileOperation) -> Result<(), String> {
+    if let Some(src) = &op.source {
+       // Inverse: Move Destination back to Source
+       if op.destination.symlink_metadata().is_ok() {
+           // Ensure parent of source exists (it should, unless we deleted it?)
+           if let Some(parent) = src.parent() {
+                let _ = fs::create_dir_all(parent);
+           }
+           move_path(&op.destination, src).map_err(|e| e.to_string())?;
+       }
+    }
+    Ok(())
+}
+
+// Renaming onto an existing file would silently replace it; that includes a dangling
+// symlink, which `exists()` does not see.
+fn ensure_free(path: &Path) -> Result<(), String> {
+    if path.symlink_metadata().is_ok() {
+        return Err(format!("Destination already exists: {}", path.display()));
+    }
+    Ok(())
+}
+
+fn create_parent(path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
     Ok(())
 }
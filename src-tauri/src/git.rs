@@ -0,0 +1,109 @@
+// What moving a git repository could lose or break, asked of the `git` command
+// line tool. Moving never loses commits, but it can strand work nobody pushed
+// and breaks links that git stores as paths (linked worktrees, submodule git dirs).
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug, Default)]
+pub struct RepoState {
+    pub uncommitted: usize, // Changed, staged or untracked paths
+    pub unpushed: usize, // Commits on no remote
+    pub stashes: usize,
+    pub links: Vec<String>, // Paths git recorded that break when the repository moves
+    pub error: Option<String>, // Could not be inspected: assume the worst
+}
+
+impl RepoState {
+    pub fn is_dirty(&self) -> bool {
+        self.uncommitted > 0 || self.error.is_some()
+    }
+
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if let Some(err) = &self.error {
+            warnings.push(format!("Could not inspect git repository: {}", err));
+        }
+        if self.uncommitted > 0 {
+            warnings.push(format!("{} uncommitted changes", self.uncommitted));
+        }
+        if self.unpushed > 0 {
+            warnings.push(format!("{} commits not pushed to any remote", self.unpushed));
+        }
+        if self.stashes > 0 {
+            warnings.push(format!("{} stashes", self.stashes));
+        }
+        warnings.extend(self.links.iter().cloned());
+        warnings
+    }
+}
+
+/// `None` if `dir` is not the top of a git repository or worktree.
+pub fn inspect(dir: &Path) -> Option<RepoState> {
+    let dot_git = dir.join(".git");
+    let meta = dot_git.symlink_metadata().ok()?;
+
+    let mut state = RepoState::default();
+    match git(dir, &["status", "--porcelain", "--ignore-submodules=none"]) {
+        Ok(out) => state.uncommitted = out.lines().count(),
+        Err(err) => {
+            state.error = Some(err);
+            return Some(state);
+        }
+    }
+    // These fail on a repository without commits, which has nothing to lose anyway.
+    state.unpushed = git(dir, &["rev-list", "--count", "HEAD", "--not", "--remotes"])
+        .ok()
+        .and_then(|out| out.trim().parse().ok())
+        .unwrap_or(0);
+    state.stashes = git(dir, &["stash", "list"]).map(|out| out.lines().count()).unwrap_or(0);
+
+    if meta.is_file() {
+        // A linked worktree or a submodule: the other side records this folder's path.
+        let target = std::fs::read_to_string(&dot_git).ok()
+            .and_then(|s| s.trim().strip_prefix("gitdir:").map(|p| p.trim().to_string()))
+            .unwrap_or_default();
+        state.links.push(format!("Linked to the git directory {}; that link breaks when moved", target));
+    } else if let Ok(out) = git(dir, &["worktree", "list", "--porcelain"]) {
+        // The first entry is this working tree itself.
+        for path in out.lines().filter_map(|l| l.strip_prefix("worktree ")).skip(1) {
+            state.links.push(format!("Linked worktree at {} loses its repository when this is moved", path));
+        }
+    }
+    state.links.extend(absolute_submodule_links(dir));
+    Some(state)
+}
+
+// Submodules normally point at their git dir with a relative path, which moves
+// along with the repository. Older git versions wrote absolute ones.
+fn absolute_submodule_links(dir: &Path) -> Vec<String> {
+    let Ok(out) = git(dir, &["config", "--file", ".gitmodules", "--get-regexp", r"^submodule\..*\.path$"]) else {
+        return Vec::new();
+    };
+    out.lines()
+        .filter_map(|line| line.split_once(' ').map(|(_, path)| PathBuf::from(path)))
+        .filter_map(|sub| {
+            let pointer = std::fs::read_to_string(dir.join(&sub).join(".git")).ok()?;
+            let target = PathBuf::from(pointer.trim().strip_prefix("gitdir:")?.trim());
+            target.is_absolute().then(|| format!("Submodule {} points at {} by absolute path", sub.display(), target.display()))
+        })
+        .collect()
+}
+
+/// Unix time of the newest commit that touched `dir`, which may be a subfolder of a repository.
+pub fn last_commit_time(dir: &Path) -> Option<u64> {
+    git(dir, &["log", "-1", "--format=%ct", "--", "."]).ok()?.trim().parse().ok()
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("git: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
@@ -0,0 +1,142 @@
+// Per-directory ignore files, matched like git does: a file's rules apply to its
+// directory and everything below it, deeper files override shallower ones, and
+// `!pattern` re-includes what a parent excluded. As in ripgrep, the folders above
+// the scan root count too, and `.gitignore` only inside a git repository.
+use crate::listing::DirListing;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Read in this order within a directory, so later files win on conflicting lines.
+pub const IGNORE_FILE_NAMES: &[&str] = &[".gitignore", ".ignore", ".organizerignore"];
+
+const GITIGNORE: &str = ".gitignore";
+
+struct Layer {
+    matcher: Gitignore,
+    parent: Option<Arc<Layer>>,
+    // Layers from above the scan root know it by its canonical path, while the
+    // scan spells it as given (`.`); (as given, canonical).
+    rebase: Option<Arc<(PathBuf, PathBuf)>>,
+}
+
+/// The ignore rules in effect for one directory: its own files plus all its parents'.
+#[derive(Clone, Default)]
+pub struct IgnoreRules {
+    innermost: Option<Arc<Layer>>,
+    in_repository: bool, // `.gitignore` files only apply inside a git repository
+}
+
+impl IgnoreRules {
+    /// Rules in effect at a scan `root`: the `.ignore` and `.organizerignore` files in
+    /// every folder above it, and the `.gitignore` files up to the top of its repository.
+    pub fn for_root(root: &Path) -> IgnoreRules {
+        let Ok(canonical) = std::fs::canonicalize(root) else {
+            return IgnoreRules::default();
+        };
+        let top = canonical.ancestors().find(|dir| dir.join(".git").exists()).map(Path::to_path_buf);
+        let rebase = Arc::new((root.to_path_buf(), canonical.clone()));
+        let mut parents: Vec<&Path> = canonical.ancestors().skip(1).collect();
+        parents.reverse();
+
+        let mut rules = IgnoreRules::default();
+        for dir in parents {
+            let in_repository = top.as_deref().is_some_and(|top| dir.starts_with(top));
+            let present: Vec<&str> = IGNORE_FILE_NAMES.iter().copied()
+                .filter(|n| dir.join(n).is_file() && (in_repository || *n != GITIGNORE))
+                .collect();
+            rules = rules.with_layer(dir, &present, Some(rebase.clone()));
+        }
+        IgnoreRules { in_repository: top.is_some(), ..rules }
+    }
+
+    /// Rules for the directory `listing` describes, adding its own ignore files if it has any.
+    pub fn descend(&self, listing: &DirListing) -> IgnoreRules {
+        let in_repository = self.in_repository || listing.contains(".git");
+        let present: Vec<&str> = IGNORE_FILE_NAMES.iter().copied()
+            .filter(|n| listing.contains(n) && (in_repository || *n != GITIGNORE))
+            .collect();
+        IgnoreRules { in_repository, ..self.with_layer(&listing.path, &present, None) }
+    }
+
+    fn with_layer(&self, dir: &Path, present: &[&str], rebase: Option<Arc<(PathBuf, PathBuf)>>) -> IgnoreRules {
+        if present.is_empty() {
+            return self.clone();
+        }
+        let mut builder = GitignoreBuilder::new(dir);
+        for name in present {
+            // A bad line only loses that line; the rest of the file still applies.
+            if let Some(err) = builder.add(dir.join(name)) {
+                log::warn!("{}", err);
+            }
+        }
+        match builder.build() {
+            Ok(matcher) => IgnoreRules {
+                innermost: Some(Arc::new(Layer { matcher, parent: self.innermost.clone(), rebase })),
+                in_repository: self.in_repository,
+            },
+            Err(err) => {
+                log::warn!("Ignoring ignore files in {}: {}", dir.display(), err);
+                self.clone()
+            }
+        }
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.ignored_by(path, is_dir).is_some()
+    }
+
+    /// The line that ignores `path`, and the file it comes from.
+    pub fn ignored_by(&self, path: &Path, is_dir: bool) -> Option<String> {
+        let mut layer = self.innermost.as_deref();
+        while let Some(current) = layer {
+            let rebased = current.rebase.as_deref()
+                .and_then(|(given, canonical)| path.strip_prefix(given).ok().map(|rest| canonical.join(rest)));
+            match current.matcher.matched(rebased.as_deref().unwrap_or(path), is_dir) {
+                Match::Ignore(glob) => {
+                    let from = glob.from().map(|f| f.display().to_string()).unwrap_or_default();
+                    return Some(format!("'{}' in {}", glob.original(), from));
+                }
+                Match::Whitelist(_) => return None,
+                Match::None => layer = current.parent.as_deref(),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn deeper_files_and_negations_override_parent_ignore_files() {
+        let tmp = TempDir::new().unwrap();
+        let repo = tmp.path().join("repo");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::write(tmp.path().join(".ignore"), "*.log\n").unwrap();
+        fs::write(tmp.path().join(".gitignore"), "*.txt\n").unwrap(); // Outside the repository
+        fs::write(repo.join(".gitignore"), "!keep.log\n").unwrap();
+
+        let rules = IgnoreRules::for_root(&repo).descend(&DirListing::read(&repo));
+        assert!(rules.is_ignored(&repo.join("debug.log"), false));
+        assert!(!rules.is_ignored(&repo.join("keep.log"), false));
+        assert!(!rules.is_ignored(&repo.join("notes.txt"), false));
+    }
+
+    #[test]
+    fn parent_gitignore_applies_to_a_subfolder_of_the_repository() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".git")).unwrap();
+        fs::create_dir(tmp.path().join("sub")).unwrap();
+        fs::write(tmp.path().join(".gitignore"), "/sub/build/\n").unwrap();
+
+        let sub = tmp.path().join("sub");
+        let rules = IgnoreRules::for_root(&sub).descend(&DirListing::read(&sub));
+        assert!(rules.is_ignored(&sub.join("build"), true));
+        assert!(!rules.is_ignored(&sub.join("src"), true));
+    }
+}
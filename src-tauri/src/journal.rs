@@ -0,0 +1,75 @@
+use crate::models::{FileOperation, HistoryEntry, Plan};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const JOURNAL_FILE_NAME: &str = "history.json";
+
+/// Applied batches persisted in the config dir, oldest first.
+/// This is what undo works from, so it survives restarts.
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn new(config_dir: &Path) -> Self {
+        Journal {
+            path: config_dir.join(JOURNAL_FILE_NAME),
+        }
+    }
+
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Corrupt history file {}: {}", self.path.display(), e))
+    }
+
+    /// `id` is the write-ahead log batch id; recording the same batch twice replaces it.
+    pub fn record(&self, id: &str, plan: &Plan, executed: Vec<FileOperation>) -> Result<HistoryEntry, String> {
+        let entry = HistoryEntry {
+            id: id.to_string(),
+            timestamp: chrono::Utc::now(),
+            root: plan.root.clone(),
+            summary: plan.summary.clone(),
+            operations: executed,
+        };
+        let mut entries = self.entries()?;
+        entries.retain(|e| e.id != id);
+        entries.push(entry.clone());
+        self.write(&entries)?;
+        Ok(entry)
+    }
+
+    pub fn last(&self) -> Result<Option<HistoryEntry>, String> {
+        Ok(self.entries()?.pop())
+    }
+
+    /// The batch in which `plan` was applied, matched by operation ids (which are
+    /// kept from the plan). `None` if it was never applied or already undone.
+    pub fn find_plan(&self, plan: &Plan) -> Result<Option<HistoryEntry>, String> {
+        let ids: Vec<&str> = plan.operations.iter().map(|op| op.id.as_str()).collect();
+        Ok(self.entries()?.into_iter()
+            .rev()
+            .find(|e| e.operations.iter().any(|op| ids.contains(&op.id.as_str()))))
+    }
+
+    /// Drop an entry once it has been undone.
+    pub fn remove(&self, id: &str) -> Result<(), String> {
+        let mut entries = self.entries()?;
+        entries.retain(|e| e.id != id);
+        self.write(&entries)
+    }
+
+    fn write(&self, entries: &[HistoryEntry]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        // Write then rename so a crash never leaves a truncated journal behind.
+        let tmp = self.path.with_extension("json.tmp");
+        let content = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+        fs::write(&tmp, content).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+}
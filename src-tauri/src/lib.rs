@@ -1,23 +1,39 @@
 pub mod models;
 pub mod config;
 pub mod projects;
+pub mod git;
+pub mod project_metadata;
 pub mod scanner;
 pub mod classifier;
+pub mod listing;
+pub mod ignore_files;
 pub mod planner;
 pub mod executor;
 pub mod commands;
 pub mod ai;
+pub mod journal;
+pub mod wal;
+pub mod transfer;
+pub mod trash;
+pub mod rules;
+pub mod template;
+pub mod sniff;
+pub mod duplicates;
 
 use commands::*;
 use std::sync::Mutex;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
-    .manage(commands::AppState {
-        undo_stack: Mutex::new(Vec::new()),
-    })
     .setup(|app| {
+      let config_dir = app.path().app_config_dir()?;
+      app.manage(commands::AppState {
+          journal: Mutex::new(journal::Journal::new(&config_dir)),
+          wal: wal::WriteAheadLog::new(&config_dir),
+          scan_cancel: Mutex::default(),
+      });
       if cfg!(debug_assertions) {
         app.handle().plugin(
           tauri_plugin_log::Builder::default()
@@ -30,13 +46,18 @@ pub fn run() {
     .invoke_handler(tauri::generate_handler![
         get_default_config,
         scan_directory,
+        cancel_scan,
+        explain_path,
         create_plan,
+        create_dedup_plan,
         apply_plan,
         undo_last_operation,
         load_config_file,
         save_config_file,
         suggest_project_name,
         get_history,
+        get_incomplete_batches,
+        recover_incomplete_batch,
         open_config_folder
     ])
     .run(tauri::generate_context!())
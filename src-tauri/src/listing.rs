@@ -0,0 +1,101 @@
+// A directory's entries, read once and shared by every check that looks at the
+// folder (classifier heuristics, marker lookups, the scanner itself).
+use glob::Pattern;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What an entry is in itself; symlinks are not followed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    File,
+    Dir,
+    Symlink,
+    Special, // FIFO, socket or device node
+}
+
+#[derive(Debug, Clone)]
+pub struct ListingEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub kind: EntryKind,
+}
+
+impl ListingEntry {
+    pub fn is_dir(&self) -> bool {
+        self.kind == EntryKind::Dir
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DirListing {
+    pub path: PathBuf,
+    pub entries: Vec<ListingEntry>, // Sorted by name, so every walk sees the same order
+}
+
+impl DirListing {
+    /// An unreadable directory gives an empty listing.
+    pub fn read(dir: &Path) -> Self {
+        let mut entries: Vec<ListingEntry> = fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|entry| {
+                let path = entry.path();
+                let kind = match entry.file_type().or_else(|_| fs::symlink_metadata(&path).map(|m| m.file_type())) {
+                    Ok(t) if t.is_symlink() => EntryKind::Symlink,
+                    Ok(t) if t.is_dir() => EntryKind::Dir,
+                    Ok(t) if t.is_file() => EntryKind::File,
+                    Ok(_) => EntryKind::Special,
+                    Err(_) => EntryKind::Special, // Vanished or unreadable: leave it alone
+                };
+                ListingEntry {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    path,
+                    kind,
+                }
+            }).collect())
+            .unwrap_or_default();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        DirListing { path: dir.to_path_buf(), entries }
+    }
+
+    /// An entry with exactly this name. Case-insensitive where the filesystem usually is.
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|e| {
+            if cfg!(any(windows, target_os = "macos")) {
+                e.name.eq_ignore_ascii_case(name)
+            } else {
+                e.name == name
+            }
+        })
+    }
+
+    /// A project marker: a plain name, a glob such as `*.sln`, or a nested path
+    /// such as `src-tauri/tauri.conf.json` (looked up on disk).
+    pub fn has_marker(&self, marker: &str) -> bool {
+        if marker.contains('/') || marker.contains('\\') {
+            return crate::projects::search_for_marker(&self.path, marker);
+        }
+        if marker.contains('*') || marker.contains('?') || marker.contains('[') {
+            return Pattern::new(marker).is_ok_and(|p| self.entries.iter().any(|e| p.matches(&e.name)));
+        }
+        self.contains(marker)
+    }
+
+    /// Lowercased extensions of the first `limit` entries.
+    pub fn extensions(&self, limit: usize) -> impl Iterator<Item = String> + '_ {
+        self.entries.iter()
+            .take(limit)
+            .filter_map(|e| Path::new(&e.name).extension().map(|x| x.to_string_lossy().to_lowercase()))
+    }
+}
+
+/// (device, inode) of what `path` points to, to recognise the same directory or
+/// file reached twice. `None` where the platform has no such identity.
+#[cfg(unix)]
+pub fn file_id(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|m| (m.dev(), m.ino()))
+}
+
+#[cfg(not(unix))]
+pub fn file_id(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use chrono::{DateTime, Utc};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FileItem {
@@ -11,6 +12,10 @@ pub struct FileItem {
     pub created: u64, // timestamp
     pub modified: u64,
     pub project_root: Option<PathBuf>, // If it belongs to a project
+    #[serde(default)]
+    pub mime: Option<String>, // Sniffed from content, see sniff.rs
+    #[serde(default)]
+    pub is_symlink: bool, // The link itself; its target is never touched
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -19,6 +24,25 @@ pub struct Project {
     pub name: String,
     pub type_guess: String, // e.g., "node", "rust", "python"
     pub internal_name: Option<String>,
+    #[serde(default)]
+    pub metadata: Option<ProjectMetadata>, // None when `collect_project_metadata` is off
+}
+
+/// Measured over the project's files, leaving out ignored folders and build output.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProjectMetadata {
+    pub size: u64, // bytes
+    pub last_modified: u64, // Newest file, same timestamps as FileItem
+    pub last_commit: Option<u64>, // Newest commit touching the project
+    pub languages: Vec<LanguageShare>, // By bytes, largest first
+    pub build_artifacts: Vec<String>, // Folders such as `target` or `web/node_modules`
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LanguageShare {
+    pub language: String,
+    pub files: usize,
+    pub bytes: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -27,6 +51,36 @@ pub enum OperationType {
     Copy,
     Delete, 
     CreateDir,
+    Trash, // To the desktop trash; destination is the slot under Trash/files
+    HardLink, // Destination becomes a hard link to source
+}
+
+/// What a scan found. `cancelled` marks partial results from a scan stopped early.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanResult {
+    pub files: Vec<FileItem>,
+    pub projects: Vec<Project>,
+    pub cancelled: bool,
+    #[serde(default)]
+    pub needs_review: Vec<ReviewItem>, // Possible projects below `min_project_confidence`
+}
+
+/// A folder that looks like a project, but not enough to move it as one unasked.
+/// Left alone until the user confirms or rejects it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReviewItem {
+    pub project: Project, // What it would be if confirmed
+    pub confidence: f32,
+    pub reason: String,
+}
+
+/// Sent periodically while a scan runs.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanProgress {
+    pub dirs_visited: usize,
+    pub files_found: usize,
+    pub projects_found: usize,
+    pub current_path: PathBuf,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -36,12 +90,49 @@ pub struct FileOperation {
     pub source: Option<PathBuf>,
     pub destination: PathBuf,
     pub reason: String, // "Rule: Images", "Project: MyProject"
+    #[serde(default)]
+    pub warnings: Vec<String>, // Worth a look before applying, e.g. uncommitted changes in a repository
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Plan {
     pub operations: Vec<FileOperation>,
     pub summary: String,
+    #[serde(default)]
+    pub root: Option<PathBuf>, // Directory the plan was generated for
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub root: Option<PathBuf>,
+    pub summary: String,
+    pub operations: Vec<FileOperation>, // In execution order
+}
+
+/// A batch that was interrupted mid-apply, as reconstructed from the write-ahead log.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IncompleteBatch {
+    pub id: String,
+    pub started: DateTime<Utc>,
+    pub root: Option<PathBuf>,
+    pub summary: String,
+    pub completed: Vec<FileOperation>,
+    pub in_flight: Option<FileOperation>, // Started but not confirmed; may or may not have happened
+    pub pending: Vec<FileOperation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum DedupMode {
+    Trash,
+    HardLink, // Keep every name, share one copy on disk
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum RecoveryAction {
+    RollForward,
+    RollBack,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -49,6 +140,42 @@ pub struct Config {
     pub rules: Vec<Rule>,
     pub ignore_patterns: Vec<String>,
     pub project_markers: Vec<String>,
+    #[serde(default)]
+    pub trash_empty_folders: bool, // Only folders directly in the root
+    #[serde(default = "default_true")]
+    pub detect_mime: bool, // Read file headers to find the real type
+    #[serde(default)]
+    pub conflict_strategy: ConflictStrategy, // Unless the rule sets its own
+    #[serde(default)]
+    pub respect_ignore_files: bool, // .gitignore, .ignore and .organizerignore in the scanned tree
+    #[serde(default)]
+    pub loose_file_depth: usize, // Folder levels below the root to collect files from; 0 = root only
+    #[serde(default)]
+    pub symlink_mode: SymlinkMode,
+    #[serde(default)]
+    pub custom_classifiers: Vec<CustomClassifier>,
+    #[serde(default = "default_min_project_confidence")]
+    pub min_project_confidence: f32, // Weaker project matches go to review instead
+    #[serde(default)]
+    pub confirmed_projects: Vec<PathBuf>, // Reviewed: always a project
+    #[serde(default)]
+    pub rejected_projects: Vec<PathBuf>, // Reviewed: a plain folder, scanned like any other
+    #[serde(default = "crate::config::default_project_destinations")]
+    pub project_destinations: Vec<ProjectDestination>,
+    #[serde(default)]
+    pub refuse_dirty_repos: bool, // Leave git projects with uncommitted changes where they are
+    #[serde(default)]
+    pub refuse_linked_repos: bool, // ...and those whose worktree or submodule links would break
+    #[serde(default = "default_true")]
+    pub collect_project_metadata: bool, // Walk each project for size, languages and last activity
+}
+
+fn default_true() -> bool {
+    true
+}
+
+pub fn default_min_project_confidence() -> f32 {
+    0.75 // Between weak markers (0.6) and everything else
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -57,4 +184,113 @@ pub struct Rule {
     pub patterns: Vec<String>, // glob patterns
     pub destination: String, // Relative to root
     pub active: bool,
+    #[serde(default)]
+    pub action: RuleAction,
+    #[serde(default)]
+    pub name_regex: Option<String>, // Alternative to `patterns`: either one selects the file
+    #[serde(default)]
+    pub mime_types: Vec<String>, // e.g. "image/*"; also selects the file, whatever its name
+    #[serde(default)]
+    pub conditions: RuleConditions, // Must all hold for a selected file
+    #[serde(default)]
+    pub conflict_strategy: Option<ConflictStrategy>, // None: use the global one. Never applies to projects
+    #[serde(default)]
+    pub max_depth: Option<usize>, // None: use the global loose_file_depth
+}
+
+/// Extra filters on top of the name match. Unset fields are not checked.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RuleConditions {
+    pub min_size: Option<u64>, // bytes
+    pub max_size: Option<u64>,
+    pub older_than_days: Option<u64>,
+    pub newer_than_days: Option<u64>,
+    #[serde(default)]
+    pub age_basis: AgeBasis,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum AgeBasis {
+    #[default]
+    Modified,
+    Created, // Falls back to modified where the filesystem has no birth time
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Category {
+    Project,
+    InstalledProgram,
+    AppData,
+    LooseFiles,
+}
+
+/// "A folder containing all of `contains` and none of `excludes` is `category`".
+/// Entries are project-marker style: names, globs or nested paths.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomClassifier {
+    pub name: String,
+    pub contains: Vec<String>,
+    #[serde(default)]
+    pub excludes: Vec<String>,
+    pub category: Category,
+    #[serde(default)]
+    pub project_type: Option<String>, // Defaults to `name` for projects
+    #[serde(default = "default_custom_priority")]
+    pub priority: i32, // Built-ins: installed software 300, app data 200, project markers 100
+    #[serde(default = "default_true")]
+    pub active: bool,
+}
+
+fn default_custom_priority() -> i32 {
+    400 // Ahead of the built-ins: what the user declared wins over a guess
+}
+
+/// Where projects detected by one of `markers` are moved, relative to the root.
+/// A marker is compared with the project's type (the marker that detected it):
+/// exact names anywhere in the table win over globs, then the first glob wins.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectDestination {
+    pub markers: Vec<String>,
+    pub destination: String,
+}
+
+/// Symlinks are never followed while scanning; this decides whether they are organized at all.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum SymlinkMode {
+    #[default]
+    Skip,
+    MoveLink, // Organize the link like a file; the target stays where it is
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum RuleAction {
+    #[default]
+    Move,
+    Copy,
+    Delete, // Recoverable: the file is parked, not unlinked
+    Trash, // Desktop trash where available, otherwise same as Delete
+}
+
+/// What to do when a file's destination is already taken, on disk or by an
+/// earlier operation in the same plan.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum ConflictStrategy {
+    #[default]
+    Rename, // "name (1).ext"
+    Skip, // Leave the incoming file where it is
+    OverwriteOlder, // Replace the existing file if it is older, else skip; the old one is parked
+    KeepBothIfDifferent, // Rename if contents differ, skip if identical
+    MergeIfIdentical, // Drop the incoming file if identical (parked, so recoverable), else skip
+}
+
+impl ConflictStrategy {
+    pub fn label(self) -> &'static str {
+        match self {
+            ConflictStrategy::Rename => "rename",
+            ConflictStrategy::Skip => "skip",
+            ConflictStrategy::OverwriteOlder => "overwrite-older",
+            ConflictStrategy::KeepBothIfDifferent => "keep-both-if-different",
+            ConflictStrategy::MergeIfIdentical => "merge-if-identical",
+        }
+    }
 }
@@ -1,12 +1,22 @@
-use crate::models::{Config, FileItem, Project, FileOperation, OperationType, Plan};
-use std::collections::HashSet;
-use std::path::Path;
+use crate::models::{Config, ConflictStrategy, FileItem, Project, FileOperation, OperationType, Plan, RuleAction};
+use crate::executor::DELETED_DIR_NAME;
+use crate::trash::{home_trash_dir, trash_destination};
+use crate::rules::{compile_rules, CompiledRule};
+use crate::config::validate_config;
+use crate::duplicates::full_hash;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 use glob::Pattern;
 use uuid::Uuid;
 
-pub fn generate_plan(files: &[FileItem], projects: &[Project], config: &Config, root: &Path) -> Plan {
+pub fn generate_plan(files: &[FileItem], projects: &[Project], config: &Config, root: &Path) -> Result<Plan, String> {
+    validate_config(config)?;
+
     let mut operations = Vec::new();
     let mut intended_paths = HashSet::new(); // To detect internal collisions within the plan
+    let mut skipped = 0; // Conflicts resolved by leaving the item alone
+    let mut refused_repos = 0; // Git projects `refuse_*_repos` kept in place
     
     // Check for existence of destination folders (CreateDir operations implied or explicit?)
     // Usually "Move" implies "Create parent dirs if missing".
@@ -14,44 +24,7 @@ pub fn generate_plan(files: &[FileItem], projects: &[Project], config: &Config,
     
     // 1. Handle Projects
     for project in projects {
-        // Find if any rule matches the Project TYPE (we only have marker name now, e.g. "package.json")
-        // We might want to add a mapping for markers -> Folder Names.
-        // For now, let's just group by "Projects/<Type>" where Type is mapped from marker.
-        
-        let subfolder = match project.type_guess.as_str() {
-            // Frameworks
-            "next.config.js" | "next.config.mjs" => "Projects/NextJS",
-            "remix.config.js" => "Projects/Remix",
-            "angular.json" => "Projects/Angular",
-            "vue.config.js" => "Projects/Vue",
-            "vite.config.js" | "vite.config.ts" => "Projects/Vite",
-            "nest-cli.json" => "Projects/NestJS",
-            "manage.py" => "Projects/Django",
-            "src-tauri/tauri.conf.json" | "src-tauri/tauri.conf.json5" => "Projects/Tauri",
-
-            // General Languages
-            "package.json" | "node_modules" | "*.js" | "*.ts" => "Projects/Node",
-            "Cargo.toml" => "Projects/Rust",
-            "pyproject.toml" | "requirements.txt" | "venv" | "app.py" | "main.py" | "*.py" => "Projects/Python",
-            "index.html" => "Projects/Web",
-            ".obsidian" => "Documents/Vaults", // Safer to keep vaults in Docs
-            "go.mod" => "Projects/Go",
-            "pom.xml" | "build.gradle" => "Projects/Java",
-            "*.sln" => "Projects/DotNet",
-            ".git" => "Projects/Git",
-            "Makefile" | "CMakeLists.txt" => "Projects/Cpp",
-            "steam_settings" | "*.exe" | "Assets" | "ProjectSettings" => "Games",
-            "composer.json" | "index.php" | "artisan" => "Projects/PHP",
-            "Gemfile" => "Projects/Ruby",
-            "pubspec.yaml" => "Projects/Flutter",
-            "AndroidManifest.xml" | "build.gradle.kts" | "*.xcodeproj" | "*.xcworkspace" | "Package.swift" | "ionic.config.json" => "Projects/Mobile",
-            "Dockerfile" | "docker-compose.yml" | "Containerfile" | "*.tf" => "Projects/DevOps",
-            "*.Rproj" => "Projects/Data",
-            "*.ipynb" => "Projects/Jupyter",
-            "mix.exs" => "Projects/Elixir",
-            "Project.toml" => "Projects/Julia",
-            _ => "Projects/Other", // Fallback
-        };
+        let subfolder = project_subfolder(config, &project.type_guess);
         
         // Destination: Root / Projects / Node / <ProjectName>
         let base_dest = root.join(subfolder);
@@ -59,102 +32,393 @@ pub fn generate_plan(files: &[FileItem], projects: &[Project], config: &Config,
         let safe_name = project.internal_name.as_ref().map(|s| sanitize_filename(s)).unwrap_or_else(|| project.name.clone());
         
         let mut dest_path = base_dest.join(&safe_name);
-        
+        if dest_path == project.path {
+            continue; // Already in place
+        }
+        let taken = dest_path.symlink_metadata().is_ok() || intended_paths.contains(&dest_path);
+        // Folders are not compared by content: every strategy but Skip renames them.
+        // Projects match no rule, so only the global strategy applies.
+        if taken && config.conflict_strategy == ConflictStrategy::Skip {
+            log::info!("Skipping project {}: {} already exists", project.path.display(), dest_path.display());
+            skipped += 1;
+            continue;
+        }
+
         let mut counter = 1;
         
-        // Collision check
-         while dest_path.exists() || intended_paths.contains(&dest_path) || dest_path == project.path {
-            // Note: if dest_path == project.path, we usually break, 
-            // BUT if we are renaming (e.g. folder "my-app" -> "cool-app"), we should NOT break just because strict path match if names differ.
-            // Actually, if paths match, it means source == dest. 
-            // If source == dest, we can stop if names match.
-            // If names differ (rename in place?), we wouldn't be in this branch unless dest already exists? 
-            // Wait, if I rename "my-app" to "cool-app", dest_path is ".../cool-app". If that doesn't exist, we are good.
-            
-            if dest_path == project.path {
-                break;
-            }
+        // Collision check. Stops at the project's own path: it was renamed like this before.
+        while dest_path != project.path && (dest_path.symlink_metadata().is_ok() || intended_paths.contains(&dest_path)) {
             let final_name = format!("{} ({})", safe_name, counter);
             dest_path = base_dest.join(&final_name);
             counter += 1;
         }
+        if dest_path == project.path {
+            continue;
+        }
 
-        if project.path != dest_path {
-             intended_paths.insert(dest_path.clone());
-             operations.push(FileOperation {
-                 id: Uuid::new_v4().to_string(),
-                 op_type: OperationType::Move,
-                 source: Some(project.path.clone()),
-                 destination: dest_path,
-                 reason: format!("Project detected: {}", project.type_guess),
-             });
+        // Only repositories that would actually move are asked about; each check runs git.
+        let repo = crate::git::inspect(&project.path);
+        if let Some(state) = &repo {
+            let refuse = (config.refuse_dirty_repos && state.is_dirty())
+                || (config.refuse_linked_repos && !state.links.is_empty());
+            if refuse {
+                log::info!("Leaving repository {} in place: {}", project.path.display(), state.warnings().join("; "));
+                refused_repos += 1;
+                continue;
+            }
         }
+
+        intended_paths.insert(dest_path.clone());
+        operations.push(FileOperation {
+            id: Uuid::new_v4().to_string(),
+            op_type: OperationType::Move,
+            source: Some(project.path.clone()),
+            reason: with_note(
+                format!("Project detected: {}", project.type_guess),
+                config.conflict_strategy,
+                taken.then(|| project_conflict_note(config.conflict_strategy, &dest_path)),
+            ),
+            destination: dest_path,
+            warnings: repo.map(|state| state.warnings()).unwrap_or_default(),
+        });
     }
 
     // 2. Handle Files
+    let trash_dir = home_trash_dir();
+    let rules = compile_rules(&config.rules);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let mut claimed_by: HashMap<PathBuf, PathBuf> = HashMap::new(); // Destination -> file moving there
     for file in files {
-        let mut matched_rule_name = None;
-        let mut dest_subdir = None;
-
-        for rule in &config.rules {
-            if !rule.active { continue; }
-            for pattern_str in &rule.patterns {
-                if let Ok(pattern) = Pattern::new(pattern_str) {
-                    // Check strict filename first
-                    if pattern.matches(&file.name) {
-                        matched_rule_name = Some(rule.name.clone());
-                        dest_subdir = Some(rule.destination.clone());
-                        break;
-                    }
-                }
+        if file.is_dir {
+            // Only empty folders are collected, and only when asked for.
+            if config.trash_empty_folders {
+                operations.push(park(&file.path, root, trash_dir.as_deref(), &mut intended_paths, RuleAction::Trash, "Empty folder".to_string()));
             }
-            if matched_rule_name.is_some() { break; }
+            continue;
+        }
+
+        // Folder the file sits in, relative to the root; empty for loose files in the root itself.
+        let subfolder = file.path.parent().and_then(|p| p.strip_prefix(root).ok()).unwrap_or(Path::new(""));
+        let depth = subfolder.components().count();
+
+        // First matching rule wins. A name match on any rule beats a content-type match,
+        // which only decides for files whose name no rule knows (`download`, `scan0001`).
+        let in_reach = |r: &&CompiledRule| depth <= r.rule.max_depth.unwrap_or(config.loose_file_depth);
+        let Some(compiled) = rules.iter().filter(in_reach).find(|r| r.matches_by_name(file, now))
+            .or_else(|| rules.iter().filter(in_reach).find(|r| r.matches_by_content(file, now)))
+        else { continue };
+        let rule = compiled.rule;
+        let reason = if depth == 0 {
+            format!("Rule: {}", rule.name)
+        } else {
+            format!("Rule: {} (from {})", rule.name, subfolder.display())
+        };
+
+        if matches!(rule.action, RuleAction::Delete | RuleAction::Trash) {
+            operations.push(park(&file.path, root, trash_dir.as_deref(), &mut intended_paths, rule.action, reason));
+            continue;
+        }
+
+        let dest_path = root.join(compiled.destination_for(file)).join(&file.name);
+        if dest_path == file.path {
+            continue; // Already in place
         }
+        let op_type = match rule.action {
+            RuleAction::Copy => OperationType::Copy,
+            _ => OperationType::Move,
+        };
+        let strategy = rule.conflict_strategy.unwrap_or(config.conflict_strategy);
 
-        if let Some(subdir) = dest_subdir {
-            let base_dest = root.join(&subdir);
-            let mut dest_path = base_dest.join(&file.name);
-            
-            // Collision handling: Rename
-            let stem = Path::new(&file.name).file_stem().unwrap_or_default().to_string_lossy();
-            let ext = Path::new(&file.name).extension()
-                        .map(|e| format!(".{}", e.to_string_lossy()))
-                        .unwrap_or_default();
-            let mut counter = 1;
-
-            // Collision check loop
-            while dest_path.exists() || intended_paths.contains(&dest_path) || dest_path == file.path {
-                if dest_path == file.path {
-                    // It's already there! No op needed.
-                    break;
-                }
-                
-                let final_name = format!("{} ({}){}", stem, counter, ext);
-                dest_path = base_dest.join(&final_name);
-                counter += 1;
+        match resolve_conflict(file, dest_path, strategy, &intended_paths, &claimed_by) {
+            Resolution::Place(dest, note) => {
+                intended_paths.insert(dest.clone());
+                claimed_by.insert(dest.clone(), file.path.clone());
+                operations.push(FileOperation {
+                    id: Uuid::new_v4().to_string(),
+                    op_type,
+                    source: Some(file.path.clone()),
+                    destination: dest,
+                    reason: with_note(reason, strategy, note),
+                    warnings: Vec::new(),
+                });
+            }
+            Resolution::Replace(dest, note) => {
+                // The older file is parked first, so undo puts it back.
+                let reason = with_note(reason, strategy, Some(note));
+                operations.push(park(&dest, root, trash_dir.as_deref(), &mut intended_paths, RuleAction::Trash, reason.clone()));
+                intended_paths.insert(dest.clone());
+                claimed_by.insert(dest.clone(), file.path.clone());
+                operations.push(FileOperation {
+                    id: Uuid::new_v4().to_string(),
+                    op_type,
+                    source: Some(file.path.clone()),
+                    destination: dest,
+                    reason,
+                    warnings: Vec::new(),
+                });
             }
-            
-            // Avoid moving if source == dest (already broke loop above if so, but double check)
-            if file.path != dest_path {
-                 intended_paths.insert(dest_path.clone());
-                 operations.push(FileOperation {
-                     id: Uuid::new_v4().to_string(),
-                     op_type: OperationType::Move,
-                     source: Some(file.path.clone()),
-                     destination: dest_path,
-                     reason: format!("Rule: {}", matched_rule_name.unwrap()),
-                 });
+            // A copy of a file that is already there adds nothing; the original stays put.
+            Resolution::Merge(_) if op_type == OperationType::Copy => skipped += 1,
+            Resolution::Merge(note) => {
+                operations.push(park(&file.path, root, trash_dir.as_deref(), &mut intended_paths, RuleAction::Trash, with_note(reason, strategy, Some(note))));
+            }
+            Resolution::Skip(note) => {
+                log::info!("Skipping {}: {}", file.path.display(), note);
+                skipped += 1;
             }
         }
     }
 
     let count = operations.len();
-    Plan {
+    let mut summary = match skipped {
+        0 => format!("Planned {} operations", count),
+        n => format!("Planned {} operations, skipped {} conflicts", count, n),
+    };
+    if refused_repos > 0 {
+        summary.push_str(&format!(", left {} git repositories in place", refused_repos));
+    }
+    Ok(Plan {
         operations,
-        summary: format!("Planned {} operations", count),
+        summary,
+        root: Some(root.to_path_buf()),
+    })
+}
+
+enum Resolution {
+    Place(PathBuf, Option<String>), // Move or copy to this path
+    Replace(PathBuf, String), // Park what is there, then move in
+    Merge(String), // Identical file already there; drop the incoming one
+    Skip(String),
+}
+
+fn resolve_conflict(
+    file: &FileItem,
+    dest: PathBuf,
+    strategy: ConflictStrategy,
+    intended_paths: &HashSet<PathBuf>,
+    claimed_by: &HashMap<PathBuf, PathBuf>,
+) -> Resolution {
+    let taken = |p: &Path| p.symlink_metadata().is_ok() || intended_paths.contains(p);
+    if !taken(&dest) {
+        return Resolution::Place(dest, None);
+    }
+
+    // What the incoming file is compared against: the source of an earlier operation
+    // that claimed the same destination, otherwise the file on disk.
+    let claimed = claimed_by.get(&dest).cloned();
+    let on_disk = claimed.is_none() && dest.symlink_metadata().is_ok();
+    let occupant = claimed.or_else(|| on_disk.then(|| dest.clone()));
+    let existing = dest.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+    let renamed = |why: &str| {
+        let dest = next_free_name(&dest, &file.name, &taken);
+        let name = dest.file_name().unwrap_or_default().to_string_lossy().to_string();
+        Resolution::Place(dest, Some(format!("{}renamed to '{}'", why, name)))
+    };
+
+    match strategy {
+        ConflictStrategy::Rename => renamed(""),
+        ConflictStrategy::Skip => Resolution::Skip(format!("'{}' already exists", existing)),
+        ConflictStrategy::OverwriteOlder => match occupant.filter(|_| on_disk) {
+            Some(path) if is_older(&path, file) => Resolution::Replace(dest, format!("replaced older '{}'", existing)),
+            Some(_) => Resolution::Skip(format!("existing '{}' is not older", existing)),
+            // Nothing on disk to overwrite yet: another file in this plan wants the name.
+            None => renamed("name taken in this plan, "),
+        },
+        ConflictStrategy::KeepBothIfDifferent => match occupant {
+            Some(path) if is_identical(&path, &file.path) => Resolution::Skip(format!("identical to '{}'", existing)),
+            _ => renamed("differs from existing, "),
+        },
+        ConflictStrategy::MergeIfIdentical => match occupant {
+            Some(path) if is_identical(&path, &file.path) => Resolution::Merge(format!("identical to '{}', merged", existing)),
+            _ => Resolution::Skip(format!("differs from '{}'", existing)),
+        },
+    }
+}
+
+/// `name (1).ext`, `name (2).ext`, ... next to `dest`.
+fn next_free_name(dest: &Path, name: &str, taken: &impl Fn(&Path) -> bool) -> PathBuf {
+    let base_dest = dest.parent().unwrap_or(dest);
+    let stem = Path::new(name).file_stem().unwrap_or_default().to_string_lossy();
+    let ext = Path::new(name).extension()
+                .map(|e| format!(".{}", e.to_string_lossy()))
+                .unwrap_or_default();
+    let mut counter = 1;
+    loop {
+        let candidate = base_dest.join(format!("{} ({}){}", stem, counter, ext));
+        if !taken(&candidate) {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+fn is_older(existing: &Path, incoming: &FileItem) -> bool {
+    let modified = existing.symlink_metadata().ok()
+        .filter(|m| m.is_file())
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+    modified.is_some_and(|m| m < incoming.modified)
+}
+
+// Size first, so differing files are rarely read.
+fn is_identical(a: &Path, b: &Path) -> bool {
+    let (Ok(meta_a), Ok(meta_b)) = (a.symlink_metadata(), b.symlink_metadata()) else { return false };
+    if !meta_a.is_file() || !meta_b.is_file() || meta_a.len() != meta_b.len() {
+        return false;
+    }
+    matches!((full_hash(a), full_hash(b)), (Ok(x), Ok(y)) if x == y)
+}
+
+/// `config.project_destinations` entry for a project detected by `type_guess`.
+fn project_subfolder<'a>(config: &'a Config, type_guess: &str) -> &'a str {
+    let destinations = &config.project_destinations;
+    destinations.iter()
+        .find(|d| d.markers.iter().any(|m| m == type_guess))
+        .or_else(|| destinations.iter().find(|d| {
+            d.markers.iter().any(|m| Pattern::new(m).is_ok_and(|p| p.matches(type_guess)))
+        }))
+        .map(|d| d.destination.as_str())
+        .unwrap_or("Projects/Other")
+}
+
+// Says when the configured strategy could not be applied to a folder as such.
+fn project_conflict_note(strategy: ConflictStrategy, dest: &Path) -> String {
+    let name = dest.file_name().unwrap_or_default().to_string_lossy();
+    match strategy {
+        ConflictStrategy::Rename => format!("renamed to '{}'", name),
+        _ => format!("folders are not compared, renamed to '{}'", name),
+    }
+}
+
+fn with_note(reason: String, strategy: ConflictStrategy, note: Option<String>) -> String {
+    match note {
+        Some(note) => format!("{} (conflict, {}: {})", reason, strategy.label(), note),
+        None => reason,
+    }
+}
+
+/// Recoverable removal: the desktop trash if asked for and available, else the deleted-items area.
+pub(crate) fn park(path: &Path, root: &Path, trash_dir: Option<&Path>, intended_paths: &mut HashSet<PathBuf>, action: RuleAction, reason: String) -> FileOperation {
+    let id = Uuid::new_v4().to_string();
+    let (op_type, destination) = match (action, trash_dir) {
+        (RuleAction::Trash, Some(trash_dir)) => {
+            let dest = trash_destination(trash_dir, path, intended_paths);
+            intended_paths.insert(dest.clone());
+            (OperationType::Trash, dest)
+        }
+        // Own subfolder per item so same-named items never collide.
+        _ => (OperationType::Delete, root.join(DELETED_DIR_NAME).join(&id).join(path.file_name().unwrap_or_default())),
+    };
+    FileOperation {
+        id,
+        op_type,
+        source: Some(path.to_path_buf()),
+        destination,
+        reason,
+        warnings: Vec::new(),
     }
 }
 
 fn sanitize_filename(name: &str) -> String {
     name.replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_' && c != ' ', "_")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::default_config;
+    use std::fs::{self, File};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    const INCOMING_MODIFIED: u64 = 1_700_000_000;
+
+    // `a.txt` in the root, bound for `Documents/a.txt` where `existing` already is.
+    fn plan_conflict(strategy: ConflictStrategy, existing: &str, existing_modified: u64) -> (TempDir, Plan) {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(root.join("a.txt"), "new").unwrap();
+        fs::create_dir(root.join("Documents")).unwrap();
+        fs::write(root.join("Documents/a.txt"), existing).unwrap();
+        File::options().write(true).open(root.join("Documents/a.txt")).unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(existing_modified)).unwrap();
+
+        let file = FileItem {
+            path: root.join("a.txt"),
+            name: "a.txt".to_string(),
+            extension: Some("txt".to_string()),
+            size: 3,
+            is_dir: false,
+            created: INCOMING_MODIFIED,
+            modified: INCOMING_MODIFIED,
+            project_root: None,
+            mime: None,
+            is_symlink: false,
+        };
+        let mut config = default_config();
+        config.conflict_strategy = strategy;
+        let plan = generate_plan(&[file], &[], &config, root).unwrap();
+        (tmp, plan)
+    }
+
+    #[test]
+    fn each_strategy_against_an_existing_destination() {
+        let older = INCOMING_MODIFIED - 1000;
+        let newer = INCOMING_MODIFIED + 1000;
+        // (strategy, existing contents, existing mtime, operations, where `a.txt` ends up, reason note)
+        type Case = (ConflictStrategy, &'static str, u64, usize, Option<&'static str>, &'static str);
+        let cases: &[Case] = &[
+            (ConflictStrategy::Rename, "old", older, 1, Some("a (1).txt"), "rename: renamed to 'a (1).txt'"),
+            (ConflictStrategy::Skip, "old", older, 0, None, ""),
+            (ConflictStrategy::OverwriteOlder, "old", older, 2, Some("a.txt"), "overwrite-older: replaced older 'a.txt'"),
+            (ConflictStrategy::OverwriteOlder, "old", newer, 0, None, ""),
+            (ConflictStrategy::KeepBothIfDifferent, "old", older, 1, Some("a (1).txt"), "keep-both-if-different: differs from existing, renamed to 'a (1).txt'"),
+            (ConflictStrategy::KeepBothIfDifferent, "new", older, 0, None, ""),
+            (ConflictStrategy::MergeIfIdentical, "new", older, 1, None, "merge-if-identical: identical to 'a.txt', merged"),
+            (ConflictStrategy::MergeIfIdentical, "old", older, 0, None, ""),
+        ];
+        for &(strategy, existing, modified, count, placed_as, note) in cases {
+            let case = format!("{:?} against '{}'", strategy, existing);
+            let (tmp, plan) = plan_conflict(strategy, existing, modified);
+            assert_eq!(plan.operations.len(), count, "{}", case);
+            let placed = plan.operations.iter().find(|op| op.op_type == OperationType::Move);
+            assert_eq!(
+                placed.map(|op| op.destination.clone()),
+                placed_as.map(|name| tmp.path().join("Documents").join(name)),
+                "{}", case,
+            );
+            match plan.operations.last() {
+                Some(op) => assert!(op.reason.contains(note), "{}: {}", case, op.reason),
+                None => assert!(plan.summary.contains("skipped 1 conflicts"), "{}: {}", case, plan.summary),
+            }
+        }
+    }
+
+    #[test]
+    fn project_conflicts_say_the_strategy_fell_back_to_renaming() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("Projects/Node/app")).unwrap();
+        fs::create_dir(root.join("app")).unwrap();
+        let project = Project {
+            path: root.join("app"),
+            name: "app".to_string(),
+            type_guess: "package.json".to_string(),
+            internal_name: None,
+            metadata: None,
+        };
+        let mut config = default_config();
+
+        config.conflict_strategy = ConflictStrategy::OverwriteOlder;
+        let plan = generate_plan(&[], std::slice::from_ref(&project), &config, root).unwrap();
+        assert_eq!(plan.operations[0].destination, root.join("Projects/Node/app (1)"));
+        assert!(plan.operations[0].reason.contains("overwrite-older: folders are not compared, renamed to 'app (1)'"));
+
+        config.conflict_strategy = ConflictStrategy::Skip;
+        let plan = generate_plan(&[], &[project], &config, root).unwrap();
+        assert!(plan.operations.is_empty());
+        assert!(plan.summary.contains("skipped 1 conflicts"));
+    }
+}
@@ -0,0 +1,104 @@
+// Facts about a detected project for sorting and filtering: how big it is, when it
+// was last touched, what it is written in, and whether it has build output lying around.
+use crate::models::{LanguageShare, ProjectMetadata};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+const MAX_LANGUAGES: usize = 5;
+
+/// Folders that hold generated output or installed dependencies. Their presence is
+/// reported; their contents are not counted towards the project's size.
+pub const BUILD_ARTIFACT_DIRS: &[&str] = &[
+    "node_modules", "target", "dist", "build", ".next", ".nuxt", ".svelte-kit",
+    "__pycache__", ".venv", "venv", ".tox", ".pytest_cache", ".gradle", "obj",
+    ".dart_tool", "_build", "cmake-build-debug", "cmake-build-release",
+];
+
+fn language(extension: &str) -> Option<&'static str> {
+    Some(match extension {
+        "rs" => "Rust",
+        "ts" | "tsx" | "mts" | "cts" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "py" | "pyi" => "Python",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "scala" => "Scala",
+        "c" | "h" => "C",
+        "cpp" | "cc" | "cxx" | "hpp" | "hh" => "C++",
+        "cs" => "C#",
+        "fs" => "F#",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "swift" => "Swift",
+        "m" | "mm" => "Objective-C",
+        "dart" => "Dart",
+        "ex" | "exs" => "Elixir",
+        "erl" => "Erlang",
+        "hs" => "Haskell",
+        "jl" => "Julia",
+        "r" => "R",
+        "lua" => "Lua",
+        "sh" | "bash" | "zsh" => "Shell",
+        "ps1" => "PowerShell",
+        "html" | "htm" => "HTML",
+        "css" | "scss" | "sass" | "less" => "CSS",
+        "vue" => "Vue",
+        "svelte" => "Svelte",
+        "sql" => "SQL",
+        "ipynb" => "Jupyter Notebook",
+        "gd" => "GDScript",
+        "tf" => "HCL",
+        _ => return None,
+    })
+}
+
+/// Running totals while a project's tree is walked.
+#[derive(Default)]
+pub struct Tally {
+    size: u64,
+    last_modified: u64,
+    languages: HashMap<&'static str, (usize, u64)>, // files, bytes
+    build_artifacts: Vec<String>,
+}
+
+impl Tally {
+    pub fn add_file(&mut self, path: &Path) {
+        let Ok(meta) = path.symlink_metadata() else { return };
+        self.size += meta.len();
+        let modified = meta.modified().ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.last_modified = self.last_modified.max(modified);
+
+        let extension = path.extension().map(|x| x.to_string_lossy().to_lowercase()).unwrap_or_default();
+        if let Some(language) = language(&extension) {
+            let entry = self.languages.entry(language).or_default();
+            entry.0 += 1;
+            entry.1 += meta.len();
+        }
+    }
+
+    /// `relative` is the folder's path inside the project, e.g. `web/node_modules`.
+    pub fn add_build_artifact(&mut self, relative: &Path) {
+        self.build_artifacts.push(relative.to_string_lossy().to_string());
+    }
+
+    pub fn finish(self, last_commit: Option<u64>) -> ProjectMetadata {
+        let mut languages: Vec<LanguageShare> = self.languages.into_iter()
+            .map(|(language, (files, bytes))| LanguageShare { language: language.to_string(), files, bytes })
+            .collect();
+        // Name breaks ties so the order does not depend on the hash map.
+        languages.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.language.cmp(&b.language)));
+        languages.truncate(MAX_LANGUAGES);
+        ProjectMetadata {
+            size: self.size,
+            last_modified: self.last_modified,
+            last_commit,
+            languages,
+            build_artifacts: self.build_artifacts,
+        }
+    }
+}
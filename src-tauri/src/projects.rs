@@ -4,54 +4,178 @@ use glob::glob;
 pub fn search_for_marker(dir: &Path, marker: &str) -> bool {
     if marker.contains('*') || marker.contains('?') {
         let pattern = dir.join(marker).to_string_lossy().to_string();
-        if let Ok(paths) = glob(&pattern) {
-            for _ in paths {
-                return true;
-            }
-        }
-        false
+        glob(&pattern).is_ok_and(|mut paths| paths.next().is_some())
     } else {
         dir.join(marker).exists()
     }
 }
 
+type NameSource = (&'static [&'static str], fn(&Path) -> Option<String>);
 
+// The source for the project's own type (the marker that detected it) is read
+// first, then the others in this order.
+const NAME_SOURCES: &[NameSource] = &[
+    (&["package.json"], package_json),
+    (&["Cargo.toml"], cargo_toml),
+    (&["pyproject.toml"], pyproject_toml),
+    (&["go.mod"], go_mod),
+    (&["pom.xml"], pom_xml),
+    (&["composer.json"], composer_json),
+    (&["pubspec.yaml"], pubspec_yaml),
+    (&["mix.exs"], mix_exs),
+    (&["*.csproj"], csproj),
+    (&["*.gemspec", "Gemfile"], gemspec),
+    (&["settings.gradle", "build.gradle", "build.gradle.kts"], settings_gradle),
+    (&[".git"], git_remote),
+];
 
+/// The name the project gives itself, from its manifest or its git remote.
+pub fn extract_internal_name(dir: &Path, type_guess: &str) -> Option<String> {
+    let is_own = |(markers, _): &&NameSource| markers.contains(&type_guess);
+    let own = NAME_SOURCES.iter().filter(is_own);
+    let others = NAME_SOURCES.iter().filter(|s| !is_own(s));
+    own.chain(others)
+        .filter_map(|(_, source)| source(dir))
+        .map(|name| name.trim().to_string())
+        .find(|name| !name.is_empty())
+}
 
+fn read(dir: &Path, file: &str) -> Option<String> {
+    std::fs::read_to_string(dir.join(file)).ok()
+}
 
-pub fn extract_internal_name(dir: &Path, type_guess: &str) -> Option<String> {
-    match type_guess {
-        "package.json" => {
-            let path = dir.join("package.json");
-            if let Ok(content) = std::fs::read_to_string(path) {
-                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                    return json["name"].as_str().map(|s| s.to_string());
-                }
+// First file in `dir` with this extension, in name order.
+fn find_by_extension(dir: &Path, extension: &str) -> Option<std::path::PathBuf> {
+    let mut found: Vec<_> = std::fs::read_dir(dir).ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|x| x == extension) && p.is_file())
+        .collect();
+    found.sort();
+    found.into_iter().next()
+}
+
+fn package_json(dir: &Path) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(&read(dir, "package.json")?).ok()?;
+    json["name"].as_str().map(str::to_string)
+}
+
+fn cargo_toml(dir: &Path) -> Option<String> {
+    // Only [package]: a workspace root has no name of its own.
+    let toml: toml::Table = read(dir, "Cargo.toml")?.parse().ok()?;
+    toml.get("package")?.get("name")?.as_str().map(str::to_string)
+}
+
+fn pyproject_toml(dir: &Path) -> Option<String> {
+    let toml: toml::Table = read(dir, "pyproject.toml")?.parse().ok()?;
+    let name = |table: &str| toml.get(table)?.get("name")?.as_str().map(str::to_string);
+    name("project").or_else(|| toml.get("tool")?.get("poetry")?.get("name")?.as_str().map(str::to_string))
+}
+
+/// Last element of the module path, without a major version suffix:
+/// `github.com/acme/widget/v2` is `widget`.
+fn go_mod(dir: &Path) -> Option<String> {
+    let content = read(dir, "go.mod")?;
+    let path = content.lines()
+        .map(|line| line.split("//").next().unwrap_or_default().trim())
+        .find_map(|line| line.strip_prefix("module").filter(|rest| rest.starts_with(char::is_whitespace)))?
+        .trim()
+        .trim_matches('"');
+    let mut segments = path.rsplit('/');
+    let last = segments.next()?;
+    let is_version = last.strip_prefix('v').is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    if is_version { segments.next() } else { Some(last) }.map(str::to_string)
+}
+
+fn pom_xml(dir: &Path) -> Option<String> {
+    let content = read(dir, "pom.xml")?;
+    let doc = roxmltree::Document::parse(&content).ok()?;
+    // The project's own artifactId, not the one inside <parent> or <dependencies>.
+    doc.root_element().children()
+        .find(|n| n.has_tag_name("artifactId"))?
+        .text()
+        .map(str::to_string)
+}
+
+/// `vendor/package` names only the package part.
+fn composer_json(dir: &Path) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(&read(dir, "composer.json")?).ok()?;
+    let name = json["name"].as_str()?;
+    Some(name.rsplit('/').next().unwrap_or(name).to_string())
+}
+
+fn pubspec_yaml(dir: &Path) -> Option<String> {
+    let yaml: serde_yaml::Value = serde_yaml::from_str(&read(dir, "pubspec.yaml")?).ok()?;
+    yaml.get("name")?.as_str().map(str::to_string)
+}
+
+// mix.exs is Elixir code; the `app:` keyword in `project/0` is as close to data as it gets.
+fn mix_exs(dir: &Path) -> Option<String> {
+    lazy_static::lazy_static! {
+        static ref APP: regex::Regex = regex::Regex::new(r"\bapp:\s*:([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    }
+    APP.captures(&read(dir, "mix.exs")?).map(|cap| cap[1].to_string())
+}
+
+/// `<AssemblyName>`, else the project file's name.
+fn csproj(dir: &Path) -> Option<String> {
+    let path = find_by_extension(dir, "csproj")?;
+    let assembly_name = std::fs::read_to_string(&path).ok().and_then(|content| {
+        let doc = roxmltree::Document::parse(&content).ok()?;
+        let node = doc.descendants().find(|n| n.has_tag_name("AssemblyName"))?;
+        node.text().map(str::to_string)
+    });
+    assembly_name.or_else(|| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+}
+
+/// `spec.name = "..."`, else the gemspec's file name.
+fn gemspec(dir: &Path) -> Option<String> {
+    lazy_static::lazy_static! {
+        static ref NAME: regex::Regex = regex::Regex::new(r#"(?m)^\s*\w+\.name\s*=\s*["']([^"']+)["']"#).unwrap();
+    }
+    let path = find_by_extension(dir, "gemspec")?;
+    let name = std::fs::read_to_string(&path).ok().and_then(|content| NAME.captures(&content).map(|cap| cap[1].to_string()));
+    name.or_else(|| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+}
+
+fn settings_gradle(dir: &Path) -> Option<String> {
+    lazy_static::lazy_static! {
+        static ref ROOT_NAME: regex::Regex = regex::Regex::new(r#"(?m)^\s*rootProject\.name\s*=\s*["']([^"']+)["']"#).unwrap();
+    }
+    let content = read(dir, "settings.gradle").or_else(|| read(dir, "settings.gradle.kts"))?;
+    ROOT_NAME.captures(&content).map(|cap| cap[1].to_string())
+}
+
+/// Repository name from the `origin` remote (or the first remote with a URL).
+fn git_remote(dir: &Path) -> Option<String> {
+    let git = dir.join(".git");
+    // Submodules and worktrees have a `.git` file pointing at the real git dir.
+    let git_dir = if git.is_file() {
+        let pointer = std::fs::read_to_string(&git).ok()?;
+        dir.join(pointer.trim().strip_prefix("gitdir:")?.trim())
+    } else {
+        git
+    };
+    // A worktree's own git dir only points at the main one, which has the config.
+    let config = std::fs::read_to_string(git_dir.join("config")).ok().or_else(|| {
+        let common = std::fs::read_to_string(git_dir.join("commondir")).ok()?;
+        std::fs::read_to_string(git_dir.join(common.trim()).join("config")).ok()
+    })?;
+
+    let mut section = String::new();
+    let mut urls = Vec::new();
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            section = line.trim_matches(|c| c == '[' || c == ']').to_string();
+        } else if let Some((key, value)) = line.split_once('=') {
+            if section.starts_with("remote ") && key.trim() == "url" {
+                urls.push((section == "remote \"origin\"", value.trim().to_string()));
             }
-        },
-        "Cargo.toml" => {
-             let path = dir.join("Cargo.toml");
-             if let Ok(content) = std::fs::read_to_string(path) {
-                 // Simple regex or parse. Regex is lighter than pulling full toml crate for just this.
-                 // [package] ... name = "foo"
-                 if let Ok(re) = regex::Regex::new(r#"(?m)^name\s*=\s*"([^"]+)""#) {
-                     if let Some(cap) = re.captures(&content) {
-                         return Some(cap[1].to_string());
-                     }
-                 }
-             }
-        },
-        "pyproject.toml" => {
-             let path = dir.join("pyproject.toml");
-             if let Ok(content) = std::fs::read_to_string(path) {
-                 if let Ok(re) = regex::Regex::new(r#"(?m)^name\s*=\s*"([^"]+)""#) {
-                      if let Some(cap) = re.captures(&content) {
-                          return Some(cap[1].to_string());
-                      }
-                 }
-             }
-        },
-        _ => {}
+        }
     }
-    None
+    // Origin first; otherwise config order.
+    urls.sort_by_key(|(is_origin, _)| !is_origin);
+    let url = &urls.first()?.1;
+    let name = url.trim_end_matches('/').rsplit(['/', ':', '\\']).next()?;
+    Some(name.strip_suffix(".git").unwrap_or(name).to_string())
 }
@@ -0,0 +1,89 @@
+use crate::models::{AgeBasis, FileItem, Rule};
+use crate::template::DestinationTemplate;
+use glob::Pattern;
+use regex::Regex;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A `Rule` with its globs, regex and destination template compiled once per plan
+/// instead of once per file.
+pub struct CompiledRule<'a> {
+    pub rule: &'a Rule,
+    patterns: Vec<Pattern>,
+    name_regex: Option<Regex>,
+    mime_types: Vec<Pattern>,
+    destination: DestinationTemplate,
+}
+
+impl<'a> CompiledRule<'a> {
+    pub fn new(rule: &'a Rule) -> Result<Self, String> {
+        let name_regex = match &rule.name_regex {
+            Some(re) => Some(Regex::new(re).map_err(|e| format!("Rule '{}': invalid regex: {}", rule.name, e))?),
+            None => None,
+        };
+        Ok(CompiledRule {
+            rule,
+            // Invalid globs never matched anything before; keep it that way.
+            patterns: rule.patterns.iter().filter_map(|p| Pattern::new(p).ok()).collect(),
+            name_regex,
+            mime_types: rule.mime_types.iter()
+                .map(|m| Pattern::new(m).map_err(|e| format!("Rule '{}': invalid MIME pattern '{}': {}", rule.name, m, e)))
+                .collect::<Result<_, _>>()?,
+            destination: DestinationTemplate::parse(&rule.destination)
+                .map_err(|e| format!("Rule '{}': {}", rule.name, e))?,
+        })
+    }
+
+    /// The rule's destination with placeholders filled in for `file`, relative to the root.
+    pub fn destination_for(&self, file: &FileItem) -> String {
+        self.destination.expand(file, self.rule.conditions.age_basis)
+    }
+
+    /// Matched by a name pattern or the name regex. `now` is in seconds since the
+    /// Unix epoch, like `FileItem` timestamps.
+    pub fn matches_by_name(&self, file: &FileItem, now: u64) -> bool {
+        self.matches_name(&file.name) && self.matches_conditions(file, now)
+    }
+
+    /// Matched only by the sniffed content type.
+    pub fn matches_by_content(&self, file: &FileItem, now: u64) -> bool {
+        self.matches_mime(file.mime.as_deref()) && self.matches_conditions(file, now)
+    }
+
+    fn matches_name(&self, name: &str) -> bool {
+        self.patterns.iter().any(|p| p.matches(name))
+            || self.name_regex.as_ref().is_some_and(|re| re.is_match(name))
+    }
+
+    fn matches_mime(&self, mime: Option<&str>) -> bool {
+        mime.is_some_and(|m| self.mime_types.iter().any(|p| p.matches(m)))
+    }
+
+    fn matches_conditions(&self, file: &FileItem, now: u64) -> bool {
+        let c = &self.rule.conditions;
+        if c.min_size.is_some_and(|min| file.size < min) || c.max_size.is_some_and(|max| file.size > max) {
+            return false;
+        }
+        if c.older_than_days.is_none() && c.newer_than_days.is_none() {
+            return true;
+        }
+        let timestamp = match c.age_basis {
+            AgeBasis::Created if file.created != 0 => file.created,
+            _ => file.modified,
+        };
+        if timestamp == 0 {
+            return false; // Unknown age: don't guess
+        }
+        let age_days = now.saturating_sub(timestamp) / SECS_PER_DAY;
+        !(c.older_than_days.is_some_and(|d| age_days < d) || c.newer_than_days.is_some_and(|d| age_days >= d))
+    }
+}
+
+/// Compile the active rules, in config order. A rule that does not compile is
+/// skipped, the same way an invalid glob simply never matches.
+pub fn compile_rules(rules: &[Rule]) -> Vec<CompiledRule<'_>> {
+    rules.iter()
+        .filter(|r| r.active)
+        .filter_map(|r| CompiledRule::new(r).map_err(|e| log::warn!("{}", e)).ok())
+        .collect()
+}
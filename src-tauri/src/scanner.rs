@@ -1,84 +1,252 @@
-use crate::models::{FileItem, Project, Config};
+use crate::models::{FileItem, Project, ProjectMetadata, Config, ReviewItem, ScanProgress, ScanResult, SymlinkMode};
+use crate::project_metadata::{Tally, BUILD_ARTIFACT_DIRS};
+use crate::listing::{file_id, DirListing, EntryKind, ListingEntry};
+use crate::classifier::{Category, Classification, ClassifierChain, Explanation, ProjectVerdict};
+use crate::config::review_path;
+use crate::ignore_files::{IgnoreRules, IGNORE_FILE_NAMES};
+use rayon::prelude::*;
 use std::fs;
-use std::path::Path;
-use std::time::UNIX_EPOCH;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, UNIX_EPOCH};
 use glob::Pattern;
 
+// Progress is reported at most this often; a fast walk would otherwise flood the UI.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+type ProgressFn = Box<dyn FnMut(&ScanProgress) + Send>;
+
+struct Reporter {
+    on_progress: ProgressFn,
+    last_report: Option<Instant>,
+}
+
+type DirId = (u64, u64); // (device, inode)
+
+// What one directory contributed, followed by its subdirectories in listing order.
+#[derive(Default)]
+struct Found {
+    id: Option<DirId>,
+    files: Vec<FileItem>,
+    projects: Vec<Project>,
+    needs_review: Vec<ReviewItem>,
+    nested: Vec<Found>,
+}
+
+impl Found {
+    /// Append this directory's results and then its subdirectories' to `into`, skipping
+    /// any directory already reached under an earlier path (bind mounts). Walkers race,
+    /// so this is decided here, in listing order, rather than while walking.
+    fn flatten(self, seen: &mut HashSet<DirId>, into: &mut Found) {
+        if self.id.is_some_and(|id| !seen.insert(id)) {
+            return;
+        }
+        into.files.extend(self.files);
+        into.projects.extend(self.projects);
+        into.needs_review.extend(self.needs_review);
+        for sub in self.nested {
+            sub.flatten(seen, into);
+        }
+    }
+}
+
+/// Walks sibling directories in parallel. Each directory is read once; its listing
+/// feeds both the classifier and the walk, and results are merged in name order so
+/// every scan of the same tree gives the same output.
 pub struct Scanner {
     config: Config,
     file_items: Vec<FileItem>,
     projects: Vec<Project>,
+    needs_review: Vec<ReviewItem>,
     ignore_patterns: Vec<Pattern>,
+    ignore_sources: Vec<String>, // The patterns as written, for explanations
+    classifiers: ClassifierChain,
+    confirmed: HashSet<PathBuf>, // Review decisions, as `review_path`s
+    rejected: HashSet<PathBuf>,
+    file_depth: usize, // Deepest level any rule wants files from
+    dirs_visited: AtomicUsize,
+    files_found: AtomicUsize,
+    projects_found: AtomicUsize,
+    reporter: Option<Mutex<Reporter>>,
+    cancel: Arc<AtomicBool>,
 }
 
 impl Scanner {
     pub fn new(config: Config) -> Self {
-        let ignore_patterns = config.ignore_patterns.iter()
-            .filter_map(|p| Pattern::new(p).ok())
-            .collect();
+        let (ignore_patterns, ignore_sources) = config.ignore_patterns.iter()
+            .filter_map(|p| Pattern::new(p).ok().map(|compiled| (compiled, p.clone())))
+            .unzip();
+        let file_depth = config.rules.iter()
+            .filter(|r| r.active)
+            .filter_map(|r| r.max_depth)
+            .fold(config.loose_file_depth, usize::max);
+        let classifiers = ClassifierChain::from_config(&config);
+        let confirmed = config.confirmed_projects.iter().map(|p| review_path(p)).collect();
+        let rejected = config.rejected_projects.iter().map(|p| review_path(p)).collect();
         Scanner {
             config,
             file_items: Vec::new(),
             projects: Vec::new(),
+            needs_review: Vec::new(),
             ignore_patterns,
+            ignore_sources,
+            classifiers,
+            confirmed,
+            rejected,
+            file_depth,
+            dirs_visited: AtomicUsize::new(0),
+            files_found: AtomicUsize::new(0),
+            projects_found: AtomicUsize::new(0),
+            reporter: None,
+            cancel: Arc::default(),
+        }
+    }
+
+    pub fn with_progress(mut self, on_progress: impl FnMut(&ScanProgress) + Send + 'static) -> Self {
+        self.reporter = Some(Mutex::new(Reporter { on_progress: Box::new(on_progress), last_report: None }));
+        self
+    }
+
+    /// Setting the flag stops the walk; what was found so far is kept.
+    pub fn with_cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    fn report(&self, current: &Path, force: bool) {
+        let Some(reporter) = &self.reporter else { return };
+        // Another walker thread reporting right now is as good as reporting ourselves.
+        let mut reporter = if force {
+            reporter.lock().unwrap()
+        } else {
+            match reporter.try_lock() {
+                Ok(r) => r,
+                Err(_) => return,
+            }
+        };
+        if !force && reporter.last_report.is_some_and(|t| t.elapsed() < PROGRESS_INTERVAL) {
+            return;
         }
+        reporter.last_report = Some(Instant::now());
+        (reporter.on_progress)(&ScanProgress {
+            dirs_visited: self.dirs_visited.load(Ordering::Relaxed),
+            files_found: self.files_found.load(Ordering::Relaxed),
+            projects_found: self.projects_found.load(Ordering::Relaxed),
+            current_path: current.to_path_buf(),
+        });
     }
 
     fn is_ignored(&self, path: &Path, root: &Path) -> bool {
+        self.ignored_by(path, root).is_some()
+    }
+
+    /// Like `is_ignored`, but also true for a folder whose contents a pattern ignores:
+    /// `**/node_modules/**` matches `a/node_modules/`, never `a/node_modules` itself.
+    fn is_ignored_dir(&self, path: &Path, root: &Path) -> bool {
+        self.is_ignored(path, root) || path.strip_prefix(root).is_ok_and(|relative| {
+            let inside = format!("{}/", relative.to_string_lossy());
+            self.ignore_patterns.iter().any(|p| p.matches(&inside))
+        })
+    }
+
+    /// The ignore pattern (or built-in exclusion) that excludes `path`, if any.
+    fn ignored_by(&self, path: &Path, root: &Path) -> Option<String> {
+         // Items parked by a Delete are never organized again.
+         if path.file_name().is_some_and(|n| n == crate::executor::DELETED_DIR_NAME) {
+             return Some(format!("{} (deleted-items area)", crate::executor::DELETED_DIR_NAME));
+         }
          // Get relative path for matching
-         let relative = match path.strip_prefix(root) {
-             Ok(p) => p,
-             Err(_) => return false,
-         };
+         let relative = path.strip_prefix(root).ok()?;
          let relative_str = relative.to_string_lossy();
          
-         for pattern in &self.ignore_patterns {
+         for (pattern, source) in self.ignore_patterns.iter().zip(&self.ignore_sources) {
              if pattern.matches(&relative_str) {
-                 return true;
+                 return Some(source.clone());
              }
              // Handle "name-only" matches for convenience
              if let Some(file_name) = path.file_name() {
                  if pattern.matches(&file_name.to_string_lossy()) {
-                     return true;
+                     return Some(source.clone());
                  }
              }
          }
-         false
+         None
     }
 
     pub fn scan(&mut self, root: &Path) {
-        self.scan_recursive(root, root);
+        let mut found = Found::default();
+        self.walk(root, root, 0, &self.root_ignore_rules(root), &[]).flatten(&mut HashSet::new(), &mut found);
+        self.file_items.extend(found.files);
+        self.projects.extend(found.projects);
+        self.needs_review.extend(found.needs_review);
+        self.report(root, true);
     }
 
-    fn scan_recursive(&mut self, dir: &Path, root: &Path) {
-        if self.is_ignored(dir, root) {
-            return;
+    fn root_ignore_rules(&self, root: &Path) -> IgnoreRules {
+        if self.config.respect_ignore_files {
+            IgnoreRules::for_root(root)
+        } else {
+            IgnoreRules::default()
+        }
+    }
+
+    /// `ancestors` are the directories above `dir` on this path; meeting one of them
+    /// again (a bind mount of a parent) would loop.
+    fn walk(&self, dir: &Path, root: &Path, depth: usize, ignore_rules: &IgnoreRules, ancestors: &[DirId]) -> Found {
+        let mut found = Found { id: file_id(dir), ..Found::default() };
+        if self.is_cancelled() || self.is_ignored(dir, root) || found.id.is_some_and(|id| ancestors.contains(&id)) {
+            return Found::default();
         }
+        let ancestors: Vec<DirId> = ancestors.iter().copied().chain(found.id).collect();
+        let listing = DirListing::read(dir);
+        self.dirs_visited.fetch_add(1, Ordering::Relaxed);
+        self.report(dir, false);
 
         // CLASSIFICATION (Smart + Easy)
         // We ask the classifier what this folder is.
-        use crate::classifier::{classify_folder, Category};
 
         if dir != root {
-            let classification = classify_folder(dir, &self.config.project_markers);
+            let classification = self.classifiers.classify(&listing);
             
+            let dir_buf = dir.to_path_buf();
+            let verdict = (classification.category == Category::Project).then(|| self.verdict(dir, classification.confidence));
             match classification.category {
+                // The user said this is not a project: treat it as a plain folder.
+                Category::Project if verdict == Some(ProjectVerdict::Rejected) => {}
                 Category::Project => {
-                    // Confirmed Project.
                     if let Some(ptype) = classification.project_type {
                         let internal_name = crate::projects::extract_internal_name(dir, &ptype);
-                        self.projects.push(Project {
-                            path: dir.to_path_buf(),
+                        let project = Project {
+                            path: dir_buf,
                             name: dir.file_name().unwrap_or_default().to_string_lossy().to_string(),
                             type_guess: ptype,
                             internal_name,
-                        });
-                        return; // Stop recursion for projects
+                            metadata: self.config.collect_project_metadata.then(|| self.project_metadata(dir, root, ignore_rules)),
+                        };
+                        if verdict != Some(ProjectVerdict::NeedsReview) {
+                            // Confirmed Project.
+                            found.projects.push(project);
+                            self.projects_found.fetch_add(1, Ordering::Relaxed);
+                        } else {
+                            // Too weak to move unasked, and its files may well belong together.
+                            found.needs_review.push(ReviewItem {
+                                project,
+                                confidence: classification.confidence,
+                                reason: classification.reason,
+                            });
+                        }
+                        return found; // Stop recursion for projects
                     }
                 }
                 Category::InstalledProgram | Category::AppData => {
                     // Safety: Do not touch installed software or game data.
-                    return; // Stop recursion
+                    return found; // Stop recursion
                 }
                 Category::LooseFiles => {
                     // Continue scanning this folder for more sub-folders
@@ -86,47 +254,238 @@ impl Scanner {
             }
         }
 
-        if let Ok(entries) = fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if self.is_ignored(&path, root) {
+        let ignore_rules = if self.config.respect_ignore_files {
+            ignore_rules.descend(&listing)
+        } else {
+            ignore_rules.clone()
+        };
+        // Moving an ignore file away would change what it ignores.
+        let is_ignore_file = |entry: &ListingEntry| {
+            self.config.respect_ignore_files && !entry.is_dir() && IGNORE_FILE_NAMES.contains(&entry.name.as_str())
+        };
+
+        let mut subdirs = Vec::new();
+        for entry in &listing.entries {
+            if self.is_cancelled() {
+                break;
+            }
+            if self.is_ignored(&entry.path, root) || ignore_rules.is_ignored(&entry.path, entry.is_dir()) || is_ignore_file(entry) {
+                continue;
+            }
+
+            match entry.kind {
+                // FIFOs, sockets and devices are never organized.
+                EntryKind::Special => continue,
+                // Never followed, so no loops and no targets outside the root.
+                EntryKind::Symlink if self.config.symlink_mode == SymlinkMode::Skip => continue,
+                EntryKind::Symlink | EntryKind::File => {
+                    // GENERAL SAFETY: Only collect "loose files" near the root, and never from
+                    // inside projects or installed software (recursion stopped above).
+                    if depth <= self.file_depth {
+                        found.files.push(self.file_item(entry));
+                        self.files_found.fetch_add(1, Ordering::Relaxed);
+                    }
                     continue;
                 }
+                EntryKind::Dir => {}
+            }
+
+            if dir == root && self.config.trash_empty_folders && is_empty_dir(&entry.path) {
+                found.files.push(FileItem {
+                    path: entry.path.clone(),
+                    name: entry.name.clone(),
+                    extension: None,
+                    size: 0,
+                    is_dir: true,
+                    created: 0,
+                    modified: 0,
+                    project_root: None,
+                    mime: None,
+                    is_symlink: false,
+                });
+                continue;
+            }
+            subdirs.push(&entry.path);
+        }
 
-                if path.is_dir() {
-                    self.scan_recursive(&path, root);
-                } else {
-                    // It's a file
-                    // GENERAL SAFETY: Only collect "loose files" if we are in the ROOT directory.
-                    if dir == root {
-                        let metadata = entry.metadata().ok();
-                        let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-                        let created = metadata.as_ref().and_then(|m| m.created().ok())
-                            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-                            .map(|d| d.as_secs())
-                            .unwrap_or(0);
-                        let modified = metadata.as_ref().and_then(|m| m.modified().ok())
-                            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-                            .map(|d| d.as_secs())
-                            .unwrap_or(0);
-                        
-                        self.file_items.push(FileItem {
-                            path: path.clone(),
-                            name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
-                            extension: path.extension().map(|e| e.to_string_lossy().to_string()),
-                            size,
-                            is_dir: false,
-                            created,
-                            modified,
-                            project_root: None,
-                        });
+        // collect() keeps the input order, whichever thread finishes first.
+        found.nested = subdirs.par_iter().map(|sub| self.walk(sub, root, depth + 1, &ignore_rules, &ancestors)).collect();
+        found
+    }
+
+    /// Why `target` is or is not treated as a project, program, app data or plain
+    /// folder when scanning `root`: whatever excluded it or stopped the walk above it,
+    /// and every classifier's checks on the folder itself.
+    pub fn explain(&self, root: &Path, target: &Path) -> Explanation {
+        let mut excluded_by = None;
+        // `scripts` and `./scripts` are the same folder; only the scan's spelling differs.
+        let relative = target.strip_prefix(root).map(Path::to_path_buf)
+            .or_else(|_| review_path(target).strip_prefix(review_path(root)).map(Path::to_path_buf));
+        match relative {
+            Err(_) => excluded_by = Some(format!("Outside the scanned folder {}", root.display())),
+            Ok(relative) => {
+                // Retrace the walk from the root down to `target`.
+                let mut dir = root.to_path_buf();
+                let mut ignore_rules = self.root_ignore_rules(root);
+                for component in relative.components() {
+                    let listing = DirListing::read(&dir);
+                    if self.config.respect_ignore_files {
+                        ignore_rules = ignore_rules.descend(&listing);
+                    }
+                    if dir != root {
+                        let above = self.classifiers.classify(&listing);
+                        let verdict = (above.category == Category::Project).then(|| self.verdict(&dir, above.confidence));
+                        if verdict == Some(ProjectVerdict::NeedsReview) {
+                            excluded_by = Some(format!("Inside {} (possible project awaiting review: {}), which is not scanned further", dir.display(), above.reason));
+                            break;
+                        }
+                        if above.category != Category::LooseFiles && verdict != Some(ProjectVerdict::Rejected) {
+                            excluded_by = Some(format!("Inside {} ({:?}: {}), which is not scanned further", dir.display(), above.category, above.reason));
+                            break;
+                        }
                     }
+                    let child = dir.join(component);
+                    let is_dir = child.symlink_metadata().is_ok_and(|m| m.is_dir());
+                    excluded_by = self.ignored_by(&child, root)
+                        .map(|p| format!("Ignore pattern '{}'", p))
+                        .or_else(|| ignore_rules.ignored_by(&child, is_dir))
+                        .or_else(|| child.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink())
+                            .then(|| format!("{} is a symlink, which is never followed", child.display())));
+                    if excluded_by.is_some() {
+                        break;
+                    }
+                    dir = child;
                 }
             }
         }
+
+        let listing = DirListing::read(target);
+        let result = if target == root {
+            Classification { reason: "The scanned folder itself is never classified".to_string(), ..Classification::default() }
+        } else {
+            self.classifiers.classify(&listing)
+        };
+        let verdict = (result.category == Category::Project).then(|| self.verdict(target, result.confidence));
+        Explanation {
+            path: target.to_path_buf(),
+            excluded_by,
+            classifiers: self.classifiers.trace(&listing),
+            result,
+            verdict,
+        }
+    }
+
+    /// Whether a folder classified as a project with `confidence` is organized as one.
+    fn verdict(&self, dir: &Path, confidence: f32) -> ProjectVerdict {
+        let key = review_path(dir);
+        if self.rejected.contains(&key) {
+            ProjectVerdict::Rejected
+        } else if confidence >= self.config.min_project_confidence {
+            ProjectVerdict::Accepted
+        } else if self.confirmed.contains(&key) {
+            ProjectVerdict::Confirmed
+        } else {
+            ProjectVerdict::NeedsReview
+        }
+    }
+
+    fn project_metadata(&self, project: &Path, root: &Path, ignore_rules: &IgnoreRules) -> ProjectMetadata {
+        let mut tally = Tally::default();
+        self.measure(project, project, root, ignore_rules, &mut tally);
+        tally.finish(crate::git::last_commit_time(project))
+    }
+
+    // Same exclusions as the scan itself; symlinks are not followed here either.
+    fn measure(&self, project: &Path, dir: &Path, root: &Path, ignore_rules: &IgnoreRules, tally: &mut Tally) {
+        if self.is_cancelled() {
+            return;
+        }
+        let listing = DirListing::read(dir);
+        let ignore_rules = if self.config.respect_ignore_files {
+            ignore_rules.descend(&listing)
+        } else {
+            ignore_rules.clone()
+        };
+        for entry in &listing.entries {
+            match entry.kind {
+                EntryKind::File if !self.is_ignored(&entry.path, root) && !ignore_rules.is_ignored(&entry.path, false) => {
+                    tally.add_file(&entry.path);
+                }
+                EntryKind::Dir if BUILD_ARTIFACT_DIRS.contains(&entry.name.as_str()) => {
+                    tally.add_build_artifact(entry.path.strip_prefix(project).unwrap_or(&entry.path));
+                }
+                EntryKind::Dir if !self.is_ignored_dir(&entry.path, root) && !ignore_rules.is_ignored(&entry.path, true) => {
+                    self.measure(project, &entry.path, root, &ignore_rules, tally);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn file_item(&self, entry: &ListingEntry) -> FileItem {
+        let is_symlink = entry.kind == EntryKind::Symlink;
+        let metadata = fs::symlink_metadata(&entry.path).ok();
+        let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+        let created = metadata.as_ref().and_then(|m| m.created().ok())
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let modified = metadata.as_ref().and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        FileItem {
+            path: entry.path.clone(),
+            name: entry.name.clone(),
+            extension: entry.path.extension().map(|e| e.to_string_lossy().to_string()),
+            size,
+            is_dir: false,
+            created,
+            modified,
+            project_root: None,
+            // Sniffing would read the link's target.
+            mime: if self.config.detect_mime && !is_symlink { crate::sniff::detect_mime(&entry.path) } else { None },
+            is_symlink,
+        }
     }
     
-    pub fn get_results(self) -> (Vec<FileItem>, Vec<Project>) {
-        (self.file_items, self.projects)
+    pub fn get_results(self) -> ScanResult {
+        ScanResult {
+            cancelled: self.is_cancelled(),
+            files: self.file_items,
+            projects: self.projects,
+            needs_review: self.needs_review,
+        }
+    }
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path).map(|mut entries| entries.next().is_none()).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::default_config;
+    use tempfile::TempDir;
+
+    #[test]
+    fn project_size_skips_ignored_folders_without_walking_them() {
+        let tmp = TempDir::new().unwrap();
+        let project = tmp.path().join("site");
+        fs::create_dir_all(project.join("static/vendor")).unwrap();
+        fs::create_dir(project.join("src")).unwrap();
+        fs::write(project.join("package.json"), "{}").unwrap();
+        fs::write(project.join("src/main.js"), "main()").unwrap();
+        fs::write(project.join("static/vendor/big.js"), "x".repeat(1000)).unwrap();
+        let mut config = default_config();
+        config.ignore_patterns.push("**/static/**".to_string());
+        let scanner = Scanner::new(config);
+
+        assert!(scanner.is_ignored_dir(&project.join("static"), tmp.path()));
+        assert!(!scanner.is_ignored_dir(&project.join("src"), tmp.path()));
+        let metadata = scanner.project_metadata(&project, tmp.path(), &IgnoreRules::default());
+        assert_eq!(metadata.size, 2 + 6);
     }
 }
@@ -0,0 +1,275 @@
+// Content-based type detection from the first bytes of a file ("magic numbers"),
+// so a JPEG saved as `download` or a PDF named `.bin` is still recognised.
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// Enough to see past the first ZIP entry of an Office document.
+const HEADER_LEN: usize = 4096;
+
+/// Detected MIME type of the file at `path`, or `None` if unreadable or unknown.
+pub fn detect_mime(path: &Path) -> Option<String> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    File::open(path).ok()?.take(HEADER_LEN as u64).read_to_end(&mut header).ok()?;
+    mime_from_bytes(&header).map(str::to_string)
+}
+
+pub fn mime_from_bytes(b: &[u8]) -> Option<&'static str> {
+    let starts = |sig: &[u8]| b.starts_with(sig);
+    let at = |offset: usize, sig: &[u8]| b.len() >= offset + sig.len() && &b[offset..offset + sig.len()] == sig;
+
+    // Images
+    if starts(&[0xFF, 0xD8, 0xFF]) { return Some("image/jpeg"); }
+    if starts(b"\x89PNG\r\n\x1a\n") { return Some("image/png"); }
+    if starts(b"GIF87a") || starts(b"GIF89a") { return Some("image/gif"); }
+    if starts(b"RIFF") && at(8, b"WEBP") { return Some("image/webp"); }
+    if starts(b"II*\0") || starts(b"MM\0*") { return Some("image/tiff"); }
+    if starts(b"8BPS") { return Some("image/vnd.adobe.photoshop"); }
+    if starts(&[0x00, 0x00, 0x01, 0x00]) && is_ico(b) { return Some("image/x-icon"); }
+    if starts(b"BM") && is_bmp(b) { return Some("image/bmp"); }
+
+    // ISO base media (MP4, MOV, HEIC, AVIF, M4A): "ftyp" box then a brand
+    if at(4, b"ftyp") && b.len() >= 12 {
+        return Some(match &b[8..12] {
+            b"heic" | b"heix" | b"mif1" | b"msf1" => "image/heic",
+            b"avif" | b"avis" => "image/avif",
+            b"qt  " => "video/quicktime",
+            b"M4A " | b"M4B " => "audio/mp4",
+            b"3gp4" | b"3gp5" | b"3g2a" => "video/3gpp",
+            _ => "video/mp4",
+        });
+    }
+
+    // Audio / Video
+    if starts(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        let is_webm = b.windows(4).any(|w| w == b"webm");
+        return Some(if is_webm { "video/webm" } else { "video/x-matroska" });
+    }
+    if starts(b"RIFF") && at(8, b"AVI ") { return Some("video/x-msvideo"); }
+    if starts(b"RIFF") && at(8, b"WAVE") { return Some("audio/wav"); }
+    if starts(b"fLaC") { return Some("audio/flac"); }
+    if starts(b"OggS") { return Some("audio/ogg"); }
+    if is_id3(b) || is_mpeg_audio_frame(b) { return Some("audio/mpeg"); }
+
+    // Documents
+    if starts(b"%PDF-") { return Some("application/pdf"); }
+    if starts(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]) {
+        return Some("application/x-ole-storage"); // Legacy .doc/.xls/.ppt/.msi
+    }
+    if starts(b"{\\rtf") { return Some("application/rtf"); }
+    if starts(b"SQLite format 3\0") { return Some("application/vnd.sqlite3"); }
+
+    // Archives
+    if starts(b"PK\x03\x04") { return Some(zip_subtype(b)); }
+    if starts(b"Rar!\x1A\x07") { return Some("application/vnd.rar"); }
+    if starts(&[0x37, 0x7A, 0xBC, 0xAF, 0x27, 0x1C]) { return Some("application/x-7z-compressed"); }
+    if starts(&[0x1F, 0x8B]) { return Some("application/gzip"); }
+    if starts(b"BZh") && at(4, b"1AY&SY") && (b'1'..=b'9').contains(&b[3]) { return Some("application/x-bzip2"); }
+    if starts(&[0xFD, b'7', b'z', b'X', b'Z', 0x00]) { return Some("application/x-xz"); }
+    if starts(&[0x28, 0xB5, 0x2F, 0xFD]) { return Some("application/zstd"); }
+    if at(257, b"ustar") { return Some("application/x-tar"); }
+
+    // Executables
+    if starts(b"\x7FELF") { return Some("application/x-executable"); }
+    if starts(b"MZ") && is_pe(b) { return Some("application/vnd.microsoft.portable-executable"); }
+
+    // Text formats identified by their opening
+    let text = String::from_utf8_lossy(b);
+    let trimmed = text.trim_start_matches('\u{feff}').trim_start();
+    if trimmed.starts_with("<svg") || (trimmed.starts_with("<?xml") && trimmed.contains("<svg")) {
+        return Some("image/svg+xml");
+    }
+
+    None
+}
+
+// The signatures below are two or three bytes, which plain text starts with often
+// enough ("BMI,Weight", "ID3 tags"), so the header that follows is checked as well.
+
+fn u16_le(b: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(b.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn u32_le(b: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(b.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+// BITMAPFILEHEADER: reserved words are zero and the DIB header that follows has
+// one of the sizes Windows and OS/2 defined.
+fn is_bmp(b: &[u8]) -> bool {
+    u32_le(b, 6) == Some(0) && matches!(u32_le(b, 14), Some(12 | 40 | 52 | 56 | 64 | 108 | 124))
+}
+
+// ICONDIR with at least one entry; the first entry's reserved byte is zero, its
+// colour planes are 0 or 1 and its image data starts after the directory.
+fn is_ico(b: &[u8]) -> bool {
+    let Some(count) = u16_le(b, 4).filter(|&n| n > 0) else { return false };
+    b.get(9) == Some(&0)
+        && matches!(u16_le(b, 10), Some(0 | 1))
+        && u32_le(b, 18).is_some_and(|offset| offset >= 6 + 16 * count as u32)
+}
+
+// ID3v2 tag: major version 2-4 and a "syncsafe" size whose bytes never set the top bit.
+fn is_id3(b: &[u8]) -> bool {
+    b.starts_with(b"ID3")
+        && b.len() >= 10
+        && (2..=4).contains(&b[3])
+        && b[4] != 0xFF
+        && b[6..10].iter().all(|&x| x < 0x80)
+}
+
+// MPEG-1/2 Layer III frame header without an ID3 tag: besides the sync word, the
+// bitrate and sample rate fields must hold defined values.
+fn is_mpeg_audio_frame(b: &[u8]) -> bool {
+    b.len() >= 4
+        && b[0] == 0xFF
+        && matches!(b[1], 0xFB | 0xFA | 0xF3 | 0xF2)
+        && !matches!(b[2] >> 4, 0x0 | 0xF)
+        && (b[2] >> 2) & 0x3 != 0x3
+}
+
+// A DOS stub alone says little; Windows executables point at a "PE\0\0" header.
+fn is_pe(b: &[u8]) -> bool {
+    u32_le(b, 0x3C).is_some_and(|offset| {
+        let offset = offset as usize;
+        b.get(offset..offset + 4) == Some(b"PE\0\0")
+    })
+}
+
+// ZIP is the container for many formats. The first entry usually tells them apart:
+// ODF and EPUB store a `mimetype` file first, OOXML starts with `[Content_Types].xml`
+// and names its parts by folder (word/, xl/, ppt/).
+fn zip_subtype(b: &[u8]) -> &'static str {
+    const NAME_OFFSET: usize = 30;
+    let name_len = if b.len() >= 28 { u16::from_le_bytes([b[26], b[27]]) as usize } else { 0 };
+    let name = b.get(NAME_OFFSET..NAME_OFFSET + name_len).unwrap_or_default();
+    let contains = |needle: &[u8]| b.windows(needle.len()).any(|w| w == needle);
+
+    if name == b"mimetype" {
+        let content = &b[(NAME_OFFSET + name_len).min(b.len())..];
+        let known = [
+            "application/epub+zip",
+            "application/vnd.oasis.opendocument.text",
+            "application/vnd.oasis.opendocument.spreadsheet",
+            "application/vnd.oasis.opendocument.presentation",
+        ];
+        if let Some(mime) = known.into_iter().find(|m| content.starts_with(m.as_bytes())) {
+            return mime;
+        }
+    }
+    if contains(b"word/") {
+        return "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+    }
+    if contains(b"xl/") {
+        return "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+    }
+    if contains(b"ppt/") {
+        return "application/vnd.openxmlformats-officedocument.presentationml.presentation";
+    }
+    if contains(b"AndroidManifest.xml") {
+        return "application/vnd.android.package-archive";
+    }
+    if contains(b"META-INF/MANIFEST.MF") {
+        return "application/java-archive";
+    }
+    "application/zip"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A ZIP local file header for a stored entry, followed by its contents.
+    fn zip_entry(name: &str, contents: &[u8]) -> Vec<u8> {
+        let mut b = b"PK\x03\x04".to_vec();
+        b.extend_from_slice(&[0; 22]);
+        b.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        b.extend_from_slice(&[0, 0]);
+        b.extend_from_slice(name.as_bytes());
+        b.extend_from_slice(contents);
+        b
+    }
+
+    #[test]
+    fn recognises_complete_headers() {
+        let mut bmp = b"BM".to_vec();
+        bmp.extend_from_slice(&[0x46, 0, 0, 0, 0, 0, 0, 0, 0x36, 0, 0, 0, 40, 0, 0, 0]);
+        let ico = [0, 0, 1, 0, 1, 0, 16, 16, 0, 0, 1, 0, 32, 0, 0x68, 4, 0, 0, 22, 0, 0, 0];
+        let mut pe = b"MZ".to_vec();
+        pe.resize(0x3C, 0);
+        pe.extend_from_slice(&[0x40, 0, 0, 0, b'P', b'E', 0, 0]);
+        let mut tar = vec![0; 257];
+        tar.extend_from_slice(b"ustar\x0000");
+
+        let cases: [(&[u8], &str); 10] = [
+            (&bmp, "image/bmp"),
+            (&ico, "image/x-icon"),
+            (&[b'I', b'D', b'3', 3, 0, 0, 0, 0, 0x1f, 0x76], "audio/mpeg"),
+            (&[0xFF, 0xFB, 0x90, 0x64], "audio/mpeg"),
+            (b"\0\0\0\x18ftypheic", "image/heic"),
+            (b"\0\0\0\x18ftypisom", "video/mp4"),
+            (b"BZh91AY&SY", "application/x-bzip2"),
+            (&pe, "application/vnd.microsoft.portable-executable"),
+            (&tar, "application/x-tar"),
+            (b"\xEF\xBB\xBF  <?xml version=\"1.0\"?>\n<svg>", "image/svg+xml"),
+        ];
+        for (bytes, mime) in cases {
+            assert_eq!(mime_from_bytes(bytes), Some(mime), "{:?}", &bytes[..bytes.len().min(12)]);
+        }
+    }
+
+    #[test]
+    fn truncated_headers_are_unknown() {
+        let cases: [&[u8]; 10] = [
+            b"",
+            &[0xFF, 0xD8],
+            b"\x89PNG",
+            b"RIFF\0\0\0\0WE",
+            b"\0\0\0\x18ftyphe",
+            b"BM\0\0\0\0",
+            &[0, 0, 1, 0, 1, 0],
+            b"ID3\x03\0",
+            b"BZh9",
+            b"MZ\x90\0",
+        ];
+        for bytes in cases {
+            assert_eq!(mime_from_bytes(bytes), None, "{:?}", bytes);
+        }
+    }
+
+    #[test]
+    fn text_that_happens_to_start_with_a_short_signature_is_unknown() {
+        let cases: [&[u8]; 4] = [
+            b"BMI,Weight,Height\n22.1,70,178\n",
+            b"ID3 tags are read by most players.\n",
+            b"MZ-700 service manual, chapter 1\n",
+            b"BZh? No, that is not a bzip2 stream.\n",
+        ];
+        for bytes in cases {
+            assert_eq!(mime_from_bytes(bytes), None, "{}", String::from_utf8_lossy(bytes));
+        }
+    }
+
+    #[test]
+    fn tells_zip_based_formats_apart() {
+        let docx = [zip_entry("[Content_Types].xml", b"<Types/>"), zip_entry("word/document.xml", b"")].concat();
+        let xlsx = [zip_entry("[Content_Types].xml", b"<Types/>"), zip_entry("xl/workbook.xml", b"")].concat();
+        let pptx = [zip_entry("[Content_Types].xml", b"<Types/>"), zip_entry("ppt/presentation.xml", b"")].concat();
+        let cases = [
+            (docx, "application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+            (xlsx, "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+            (pptx, "application/vnd.openxmlformats-officedocument.presentationml.presentation"),
+            (zip_entry("mimetype", b"application/vnd.oasis.opendocument.text"), "application/vnd.oasis.opendocument.text"),
+            (zip_entry("mimetype", b"application/epub+zip"), "application/epub+zip"),
+            (zip_entry("AndroidManifest.xml", b""), "application/vnd.android.package-archive"),
+            (zip_entry("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0"), "application/java-archive"),
+            (zip_entry("mimetype", b"text/plain"), "application/zip"),
+            (zip_entry("notes.txt", b"hello"), "application/zip"),
+            // Cut off inside the first file name: still a ZIP, just not a known kind.
+            (zip_entry("[Content_Types].xml", b"")[..36].to_vec(), "application/zip"),
+        ];
+        for (bytes, mime) in cases {
+            assert_eq!(mime_from_bytes(&bytes), Some(mime), "{}", String::from_utf8_lossy(&bytes));
+        }
+    }
+}
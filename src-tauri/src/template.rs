@@ -0,0 +1,158 @@
+use crate::models::{AgeBasis, FileItem};
+use chrono::{Local, TimeZone};
+use std::path::Path;
+
+pub const PLACEHOLDERS: &[&str] = &["year", "month", "day", "ext", "size_bucket", "stem_initial"];
+
+const MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Text(String),
+    Placeholder(String),
+}
+
+/// A rule destination such as `Media/Images/{year}/{month}`, expanded per file.
+#[derive(Debug, Clone)]
+pub struct DestinationTemplate {
+    segments: Vec<Segment>,
+}
+
+impl DestinationTemplate {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            if open > 0 {
+                segments.push(Segment::Text(rest[..open].to_string()));
+            }
+            let close = rest[open..].find('}')
+                .ok_or_else(|| format!("Unclosed '{{' in destination '{}'", template))?;
+            let name = &rest[open + 1..open + close];
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(format!(
+                    "Unknown placeholder '{{{}}}' in destination '{}' (available: {})",
+                    name, template, PLACEHOLDERS.iter().map(|p| format!("{{{}}}", p)).collect::<Vec<_>>().join(", "),
+                ));
+            }
+            segments.push(Segment::Placeholder(name.to_string()));
+            rest = &rest[open + close + 1..];
+        }
+        if rest.contains('}') {
+            return Err(format!("Unmatched '}}' in destination '{}'", template));
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+        Ok(DestinationTemplate { segments })
+    }
+
+    /// Dates follow the rule's `age_basis`, in local time.
+    pub fn expand(&self, file: &FileItem, basis: AgeBasis) -> String {
+        self.segments.iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.clone(),
+                // Values become path components, so keep separators out of them.
+                Segment::Placeholder(name) => placeholder_value(name, file, basis).replace(['/', '\\'], "_"),
+            })
+            .collect()
+    }
+}
+
+fn placeholder_value(name: &str, file: &FileItem, basis: AgeBasis) -> String {
+    let timestamp = match basis {
+        AgeBasis::Created if file.created != 0 => file.created,
+        _ => file.modified,
+    };
+    let date = (timestamp != 0)
+        .then(|| Local.timestamp_opt(timestamp as i64, 0).single())
+        .flatten();
+
+    match name {
+        "year" => date.map(|d| d.format("%Y").to_string()).unwrap_or_else(|| "Unknown".to_string()),
+        "month" => date.map(|d| d.format("%m").to_string()).unwrap_or_else(|| "Unknown".to_string()),
+        "day" => date.map(|d| d.format("%d").to_string()).unwrap_or_else(|| "Unknown".to_string()),
+        "ext" => file.extension.as_ref()
+            .filter(|e| !e.is_empty())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_else(|| "other".to_string()),
+        "size_bucket" => match file.size {
+            s if s < MB => "Small",
+            s if s < 100 * MB => "Medium",
+            s if s < 1024 * MB => "Large",
+            _ => "Huge",
+        }.to_string(),
+        "stem_initial" => Path::new(&file.name).file_stem()
+            .and_then(|s| s.to_string_lossy().chars().find(|c| c.is_alphanumeric()))
+            .map(|c| c.to_uppercase().to_string())
+            .unwrap_or_else(|| "#".to_string()),
+        _ => String::new(), // Rejected by parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    // Midday UTC, so the date is the same in every time zone that matters here.
+    const JUNE_15_2024: u64 = 1_718_452_800;
+
+    fn file(name: &str, extension: Option<&str>, size: u64, modified: u64) -> FileItem {
+        FileItem {
+            path: PathBuf::from(name),
+            name: name.to_string(),
+            extension: extension.map(str::to_string),
+            size,
+            is_dir: false,
+            created: 0,
+            modified,
+            project_root: None,
+            mime: None,
+            is_symlink: false,
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_destinations() {
+        let cases = [
+            ("Media/{colour}", "Unknown placeholder '{colour}'"),
+            ("Media/{}", "Unknown placeholder '{}'"),
+            ("Media/{{year}", "Unknown placeholder '{{year}'"),
+            ("Media/{year", "Unclosed '{'"),
+            ("Media/year}", "Unmatched '}'"),
+            ("Media/{year}}", "Unmatched '}'"),
+        ];
+        for (template, error) in cases {
+            let err = DestinationTemplate::parse(template).unwrap_err();
+            assert!(err.starts_with(error), "{}: {}", template, err);
+        }
+        assert!(DestinationTemplate::parse("{nope}").unwrap_err().contains("(available: {year}, {month}"));
+    }
+
+    #[test]
+    fn expands_placeholders_with_and_without_surrounding_text() {
+        let photo = file("holiday.JPG", Some("JPG"), 3 * MB, JUNE_15_2024);
+        let cases = [
+            ("", ""),
+            ("Media/Images", "Media/Images"),
+            ("Media/{year}/{month}", "Media/2024/06"),
+            ("{year}-{month}-{day}", "2024-06-15"),
+            ("{ext}{size_bucket}", "jpgMedium"),
+            ("By letter/{stem_initial}/", "By letter/H/"),
+        ];
+        for (template, expected) in cases {
+            let expanded = DestinationTemplate::parse(template).unwrap().expand(&photo, AgeBasis::Modified);
+            assert_eq!(expanded, expected, "{}", template);
+        }
+    }
+
+    #[test]
+    fn missing_values_fall_back_instead_of_leaving_empty_segments() {
+        let template = DestinationTemplate::parse("{year}/{ext}/{stem_initial}").unwrap();
+        let bare = file("_.", Some(""), 0, 0);
+        assert_eq!(template.expand(&bare, AgeBasis::Created), "Unknown/other/#");
+        let odd = file("xray.tar", Some("a/b"), 0, JUNE_15_2024);
+        assert_eq!(template.expand(&odd, AgeBasis::Created), "2024/a_b/X");
+    }
+}
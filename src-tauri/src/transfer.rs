@@ -0,0 +1,249 @@
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// `fs::rename`, falling back to copy + verify + delete when `src` and `dst`
+/// live on different filesystems (EXDEV), e.g. a rule pointing at another drive.
+pub fn move_path(src: &Path, dst: &Path) -> io::Result<()> {
+    match fs::rename(src, dst) {
+        Err(e) if is_cross_device(&e) => move_across_devices(src, dst),
+        other => other,
+    }
+}
+
+fn is_cross_device(e: &io::Error) -> bool {
+    #[cfg(unix)]
+    const EXDEV: i32 = 18;
+    #[cfg(windows)]
+    const EXDEV: i32 = 17; // ERROR_NOT_SAME_DEVICE
+    #[cfg(not(any(unix, windows)))]
+    const EXDEV: i32 = -1;
+    e.raw_os_error() == Some(EXDEV)
+}
+
+fn move_across_devices(src: &Path, dst: &Path) -> io::Result<()> {
+    copy_path(src, dst)?;
+    finish_move(src, dst, remove_tree)
+}
+
+// Removing the source can fail halfway (a locked file, a read-only folder). While the
+// source is still whole, take the copy back so the move simply did not happen;
+// otherwise both halves stay for recovery to finish.
+fn finish_move(src: &Path, dst: &Path, remove: impl Fn(&Path) -> io::Result<()>) -> io::Result<()> {
+    let Err(e) = remove(src) else { return Ok(()) };
+    if verify_tree(src, dst).is_ok() {
+        remove_tree(dst)?;
+    }
+    Err(e)
+}
+
+/// Copy `src` to `dst` and verify the result. `dst` only appears once the copy is complete.
+pub fn copy_path(src: &Path, dst: &Path) -> io::Result<()> {
+    // Copy under a temporary name next to the destination, so an interrupted copy
+    // is never mistaken for a finished one. The final rename is same-device and atomic.
+    let staging = staging_path(dst);
+    if staging.symlink_metadata().is_ok() {
+        remove_tree(&staging)?; // Left over from an earlier interrupted attempt
+    }
+    if let Err(e) = copy_tree(src, &staging).and_then(|_| verify_tree(src, &staging)) {
+        let _ = remove_tree(&staging);
+        return Err(e);
+    }
+    fs::rename(&staging, dst)
+}
+
+fn staging_path(dst: &Path) -> PathBuf {
+    let name = dst.file_name().unwrap_or_default().to_string_lossy();
+    dst.with_file_name(format!(".{}.organizer-partial", name))
+}
+
+/// Recursively copy a file, directory or symlink, keeping permissions and mtimes.
+/// Symlinks are recreated, never followed.
+pub fn copy_tree(src: &Path, dst: &Path) -> io::Result<()> {
+    let meta = fs::symlink_metadata(src)?;
+    let file_type = meta.file_type();
+
+    if file_type.is_symlink() {
+        copy_symlink(src, dst)
+    } else if file_type.is_dir() {
+        fs::create_dir(dst)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+        // After the children, since creating them bumps the directory mtime.
+        set_dir_mtime(dst, &meta);
+        fs::set_permissions(dst, meta.permissions())
+    } else if !file_type.is_file() {
+        // Opening a FIFO would block, and device nodes have no contents to copy.
+        Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Not a regular file: {}", src.display())))
+    } else {
+        let mut reader = File::open(src)?;
+        let mut writer = File::create(dst)?;
+        io::copy(&mut reader, &mut writer)?;
+        writer.set_modified(meta.modified()?)?;
+        writer.sync_all()?;
+        drop(writer);
+        fs::set_permissions(dst, meta.permissions())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(src)?, dst)
+}
+
+#[cfg(windows)]
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    let target = fs::read_link(src)?;
+    if fs::metadata(src).map(|m| m.is_dir()).unwrap_or(false) {
+        std::os::windows::fs::symlink_dir(target, dst)
+    } else {
+        std::os::windows::fs::symlink_file(target, dst)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn copy_symlink(_src: &Path, _dst: &Path) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "symlinks are not supported on this platform"))
+}
+
+fn set_dir_mtime(dir: &Path, meta: &Metadata) {
+    // Directories can only be opened as files on Unix. Best effort: a wrong
+    // directory mtime is not worth failing the move over.
+    #[cfg(unix)]
+    if let (Ok(handle), Ok(modified)) = (File::open(dir), meta.modified()) {
+        let _ = handle.set_modified(modified);
+    }
+    #[cfg(not(unix))]
+    let _ = (dir, meta);
+}
+
+/// Check that `copy` holds exactly what `original` does, byte for byte.
+pub fn verify_tree(original: &Path, copy: &Path) -> io::Result<()> {
+    verify(original, copy, true)
+}
+
+/// Like `verify_tree`, but `copy` may hold entries `original` no longer has: what
+/// is left of a source whose removal after a cross-device move was interrupted.
+pub fn verify_remainder(original: &Path, copy: &Path) -> io::Result<()> {
+    verify(original, copy, false)
+}
+
+fn verify(original: &Path, copy: &Path, exact: bool) -> io::Result<()> {
+    let mismatch = |what: &str| io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Copy verification failed for {}: {}", original.display(), what),
+    );
+    let a = fs::symlink_metadata(original)?;
+    let b = fs::symlink_metadata(copy)?;
+
+    if a.file_type().is_symlink() {
+        if !b.file_type().is_symlink() || fs::read_link(original)? != fs::read_link(copy)? {
+            return Err(mismatch("symlink target differs"));
+        }
+    } else if a.is_dir() {
+        if !b.is_dir() {
+            return Err(mismatch("not a directory"));
+        }
+        let mut names_a: Vec<_> = fs::read_dir(original)?.map(|e| e.map(|e| e.file_name())).collect::<io::Result<_>>()?;
+        let mut names_b: Vec<_> = fs::read_dir(copy)?.map(|e| e.map(|e| e.file_name())).collect::<io::Result<_>>()?;
+        names_a.sort();
+        names_b.sort();
+        let entries_match = if exact { names_a == names_b } else { names_a.iter().all(|n| names_b.binary_search(n).is_ok()) };
+        if !entries_match {
+            return Err(mismatch("directory entries differ"));
+        }
+        for name in names_a {
+            verify(&original.join(&name), &copy.join(&name), exact)?;
+        }
+    } else {
+        if !b.is_file() || a.len() != b.len() {
+            return Err(mismatch("size differs"));
+        }
+        if !same_contents(original, copy)? {
+            return Err(mismatch("contents differ"));
+        }
+    }
+    Ok(())
+}
+
+fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    let mut fa = File::open(a)?;
+    let mut fb = File::open(b)?;
+    let mut buf_a = vec![0u8; 64 * 1024];
+    let mut buf_b = vec![0u8; 64 * 1024];
+    loop {
+        let n = read_full(&mut fa, &mut buf_a)?;
+        let m = read_full(&mut fb, &mut buf_b)?;
+        if n != m || buf_a[..n] != buf_b[..m] {
+            return Ok(false);
+        }
+        if n == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+// `read` may return short counts; fill the buffer unless we hit EOF.
+fn read_full(file: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Remove a file, symlink or whole directory tree. Symlinks are never followed.
+pub fn remove_tree(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // A source folder with two files, already copied to `dst` as `copy_path` leaves it.
+    fn copied(tmp: &TempDir) -> (PathBuf, PathBuf) {
+        let (src, dst) = (tmp.path().join("src"), tmp.path().join("dst"));
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("a.txt"), "a").unwrap();
+        fs::write(src.join("b.txt"), "b").unwrap();
+        copy_path(&src, &dst).unwrap();
+        (src, dst)
+    }
+
+    fn refuse(_: &Path) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::PermissionDenied, "locked"))
+    }
+
+    #[test]
+    fn takes_the_copy_back_when_the_source_cannot_be_removed_at_all() {
+        let tmp = TempDir::new().unwrap();
+        let (src, dst) = copied(&tmp);
+
+        assert!(finish_move(&src, &dst, refuse).is_err());
+        assert!(src.join("a.txt").exists() && src.join("b.txt").exists());
+        assert!(!dst.exists());
+    }
+
+    #[test]
+    fn keeps_both_halves_when_source_removal_stops_partway() {
+        let tmp = TempDir::new().unwrap();
+        let (src, dst) = copied(&tmp);
+
+        let partly = |p: &Path| fs::remove_file(p.join("a.txt")).and_then(|_| refuse(p));
+        assert!(finish_move(&src, &dst, partly).is_err());
+        assert!(!src.join("a.txt").exists());
+        assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "a");
+        assert!(verify_remainder(&src, &dst).is_ok());
+    }
+}
@@ -0,0 +1,106 @@
+// Home trash as described by the freedesktop.org Trash specification:
+// items go to `$XDG_DATA_HOME/Trash/files/<name>` next to `info/<name>.trashinfo`,
+// so file managers can show and restore them too.
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// `None` where the freedesktop spec does not apply (Windows, macOS).
+pub fn home_trash_dir() -> Option<PathBuf> {
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        dirs::data_dir().map(|d| d.join("Trash"))
+    } else {
+        None
+    }
+}
+
+/// Pick a free `files/<name>` slot for `item`, skipping names already taken in the
+/// trash or claimed by other operations in the same plan.
+pub fn trash_destination(trash_dir: &Path, item: &Path, claimed: &HashSet<PathBuf>) -> PathBuf {
+    let name = item.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let stem = Path::new(&name).file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let ext = Path::new(&name).extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+
+    let files = trash_dir.join("files");
+    let mut candidate = name.clone();
+    let mut counter = 2;
+    loop {
+        let dest = files.join(&candidate);
+        let taken = dest.symlink_metadata().is_ok()
+            || info_path(&dest).is_some_and(|p| p.exists())
+            || claimed.contains(&dest);
+        if !taken {
+            return dest;
+        }
+        // Same "name.2.ext" scheme most file managers use.
+        candidate = format!("{}.{}{}", stem, counter, ext);
+        counter += 1;
+    }
+}
+
+/// `<trash>/info/<name>.trashinfo` for a `<trash>/files/<name>` destination.
+pub fn info_path(dest: &Path) -> Option<PathBuf> {
+    let files = dest.parent()?;
+    let trash = files.parent()?;
+    let name = dest.file_name()?.to_string_lossy();
+    Some(trash.join("info").join(format!("{}.trashinfo", name)))
+}
+
+/// Write the `.trashinfo` for an item about to be moved to `dest`. Fails if the
+/// slot was taken in the meantime; the spec uses the info file as the lock.
+pub fn write_info(original: &Path, dest: &Path) -> io::Result<PathBuf> {
+    let info = info_path(dest)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a trash destination"))?;
+    if let Some(trash) = info.parent().and_then(Path::parent) {
+        create_trash_dirs(trash)?;
+    }
+    let original = if original.is_absolute() {
+        original.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(original)
+    };
+    let mut file = OpenOptions::new().write(true).create_new(true).open(&info)?;
+    write!(
+        file,
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode_path(&original),
+        chrono::Local::now().format("%Y-%m-%dT%H:%M:%S"),
+    )?;
+    file.sync_all()?;
+    Ok(info)
+}
+
+pub fn remove_info(dest: &Path) {
+    if let Some(info) = info_path(dest) {
+        let _ = fs::remove_file(info);
+    }
+}
+
+fn create_trash_dirs(trash: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(trash.join("files"))?;
+    builder.create(trash.join("info"))
+}
+
+// The spec stores Path as a URL-escaped string (RFC 2396), keeping '/' as is.
+fn encode_path(path: &Path) -> String {
+    #[cfg(unix)]
+    let raw = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).to_vec();
+    #[cfg(not(unix))]
+    let raw = path.to_string_lossy().into_owned().into_bytes();
+    let mut out = String::with_capacity(raw.len());
+    for b in raw {
+        if b.is_ascii_alphanumeric() || b"/-_.~".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
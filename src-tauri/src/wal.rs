@@ -0,0 +1,393 @@
+use crate::executor::{execute_plan, settle_interrupted, undo_single_op};
+use crate::journal::Journal;
+use crate::models::{FileOperation, HistoryEntry, IncompleteBatch, Plan, RecoveryAction};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const WAL_DIR_NAME: &str = "wal";
+
+// One JSON record per line. A batch file only exists while the batch is in progress,
+// so any file found at startup belongs to a batch that was interrupted.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum WalRecord {
+    Begin {
+        batch_id: String,
+        timestamp: DateTime<Utc>,
+        root: Option<PathBuf>,
+        summary: String,
+        operations: Vec<FileOperation>,
+    },
+    Intent { op: FileOperation },
+    Done { op_id: String },
+    Failed { op_id: String },
+    Undone { op_id: String },
+}
+
+pub struct WriteAheadLog {
+    dir: PathBuf,
+}
+
+impl WriteAheadLog {
+    pub fn new(config_dir: &Path) -> Self {
+        WriteAheadLog {
+            dir: config_dir.join(WAL_DIR_NAME),
+        }
+    }
+
+    pub fn begin(&self, plan: &Plan) -> Result<WalBatch, String> {
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let id = Uuid::new_v4().to_string();
+        let path = self.batch_path(&id);
+        let file = OpenOptions::new().create_new(true).append(true).open(&path)
+            .map_err(|e| e.to_string())?;
+        let mut batch = WalBatch { id: id.clone(), path, file, state: BatchState::default() };
+        batch.append(&WalRecord::Begin {
+            batch_id: id,
+            timestamp: Utc::now(),
+            root: plan.root.clone(),
+            summary: plan.summary.clone(),
+            operations: plan.operations.clone(),
+        })?;
+        Ok(batch)
+    }
+
+    /// Batches whose log was never closed, i.e. the process died mid-apply.
+    pub fn incomplete_batches(&self) -> Result<Vec<IncompleteBatch>, String> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Ok(Vec::new());
+        };
+        let mut batches = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            if let Some(batch) = read_batch(&path)? {
+                batches.push(batch);
+            } else {
+                // Crashed before the Begin record hit the disk: nothing was touched.
+                let _ = fs::remove_file(&path);
+            }
+        }
+        batches.sort_by_key(|b| b.started);
+        Ok(batches)
+    }
+
+    fn resume(&self, id: &str) -> Result<(WalBatch, IncompleteBatch), String> {
+        let path = self.batch_path(id);
+        let info = read_batch(&path)?
+            .ok_or_else(|| format!("No incomplete batch with id {}", id))?;
+        let file = OpenOptions::new().append(true).open(&path).map_err(|e| e.to_string())?;
+        let mut state = BatchState::default();
+        for op in &info.completed {
+            state.applied.push(op.id.clone());
+        }
+        state.in_flight = info.in_flight.as_ref().map(|op| op.id.clone());
+        Ok((WalBatch { id: id.to_string(), path, file, state }, info))
+    }
+
+    fn batch_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.jsonl", id))
+    }
+}
+
+#[derive(Default)]
+struct BatchState {
+    applied: Vec<String>, // op ids currently in effect, in execution order
+    in_flight: Option<String>,
+}
+
+/// Open log for a batch being executed. Every record is synced before returning.
+pub struct WalBatch {
+    id: String,
+    path: PathBuf,
+    file: File,
+    state: BatchState,
+}
+
+impl WalBatch {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn intent(&mut self, op: &FileOperation) -> Result<(), String> {
+        self.append(&WalRecord::Intent { op: op.clone() })?;
+        self.state.in_flight = Some(op.id.clone());
+        Ok(())
+    }
+
+    pub fn done(&mut self, op_id: &str) -> Result<(), String> {
+        self.append(&WalRecord::Done { op_id: op_id.to_string() })?;
+        self.state.in_flight = None;
+        self.state.applied.push(op_id.to_string());
+        Ok(())
+    }
+
+    pub fn failed(&mut self, op_id: &str) -> Result<(), String> {
+        self.append(&WalRecord::Failed { op_id: op_id.to_string() })?;
+        self.state.in_flight = None;
+        Ok(())
+    }
+
+    pub fn undone(&mut self, op_id: &str) -> Result<(), String> {
+        self.append(&WalRecord::Undone { op_id: op_id.to_string() })?;
+        self.state.applied.retain(|id| id != op_id);
+        Ok(())
+    }
+
+    /// True when nothing logged in this batch is still in effect on disk.
+    pub fn is_clean(&self) -> bool {
+        self.state.applied.is_empty() && self.state.in_flight.is_none()
+    }
+
+    /// Close the batch. Only call once its outcome is recorded elsewhere (or there is none).
+    pub fn finish(self) -> Result<(), String> {
+        drop(self.file);
+        fs::remove_file(&self.path).map_err(|e| e.to_string())
+    }
+
+    fn append(&mut self, record: &WalRecord) -> Result<(), String> {
+        let mut line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+        self.file.sync_data().map_err(|e| e.to_string())
+    }
+}
+
+fn read_batch(path: &Path) -> Result<Option<IncompleteBatch>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut header = None;
+    let mut applied: Vec<String> = Vec::new();
+    let mut in_flight: Option<String> = None;
+
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        // A torn final line means we died while writing it; its operation never started.
+        let Ok(record) = serde_json::from_str::<WalRecord>(&line) else { break };
+        match record {
+            WalRecord::Begin { batch_id, timestamp, root, summary, operations } => {
+                header = Some((batch_id, timestamp, root, summary, operations));
+            }
+            WalRecord::Intent { op } => in_flight = Some(op.id),
+            WalRecord::Done { op_id } => {
+                in_flight = None;
+                applied.push(op_id);
+            }
+            WalRecord::Failed { .. } => in_flight = None,
+            WalRecord::Undone { op_id } => applied.retain(|id| *id != op_id),
+        }
+    }
+
+    let Some((id, started, root, summary, operations)) = header else {
+        return Ok(None);
+    };
+    let by_id: HashMap<&str, &FileOperation> = operations.iter().map(|op| (op.id.as_str(), op)).collect();
+    let completed = applied.iter().filter_map(|id| by_id.get(id.as_str()).map(|op| (*op).clone())).collect();
+    let in_flight_op = in_flight.as_deref().and_then(|id| by_id.get(id)).map(|op| (*op).clone());
+    let pending = operations.iter()
+        .filter(|op| !applied.contains(&op.id) && in_flight.as_deref() != Some(op.id.as_str()))
+        .cloned()
+        .collect();
+
+    Ok(Some(IncompleteBatch {
+        id,
+        started,
+        root,
+        summary,
+        completed,
+        in_flight: in_flight_op,
+        pending,
+    }))
+}
+
+/// Execute `plan` under the write-ahead log and record it in the history once it completes.
+pub fn apply_logged(wal: &WriteAheadLog, journal: &Journal, plan: &Plan) -> Result<HistoryEntry, String> {
+    let batch = wal.begin(plan)?;
+    run_batch(batch, journal, plan, Vec::new())
+}
+
+fn run_batch(mut batch: WalBatch, journal: &Journal, plan: &Plan, mut already_done: Vec<FileOperation>) -> Result<HistoryEntry, String> {
+    match execute_plan(plan, Some(&mut batch)) {
+        Ok(executed) => {
+            already_done.extend(executed);
+            let entry = journal.record(batch.id(), plan, already_done)
+                .map_err(|e| format!("Plan applied but could not be saved to history: {}", e))?;
+            batch.finish()?;
+            Ok(entry)
+        }
+        Err(e) => {
+            // If the rollback left something behind, keep the log so it can be recovered.
+            if batch.is_clean() {
+                batch.finish()?;
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Finish an interrupted batch, either by applying what is left or by reverting what was done.
+pub fn recover(wal: &WriteAheadLog, journal: &Journal, batch_id: &str, action: RecoveryAction) -> Result<String, String> {
+    let (mut batch, info) = wal.resume(batch_id)?;
+
+    // The op that was in flight may have been cut off halfway; settle it first.
+    let mut applied = info.completed.clone();
+    let mut remaining = info.pending.clone();
+    if let Some(op) = &info.in_flight {
+        if settle_interrupted(op)? {
+            batch.done(&op.id)?;
+            applied.push(op.clone());
+        } else {
+            batch.failed(&op.id)?;
+            remaining.insert(0, op.clone());
+        }
+    }
+    match action {
+        RecoveryAction::RollForward => {
+            let plan = Plan { operations: remaining, summary: info.summary.clone(), root: info.root.clone() };
+            let entry = run_batch(batch, journal, &plan, applied)?;
+            Ok(format!("Completed interrupted batch ({} operations)", entry.operations.len()))
+        }
+        RecoveryAction::RollBack => {
+            for op in applied.iter().rev() {
+                undo_single_op(op).map_err(|e| format!("Undo error: {}", e))?;
+                batch.undone(&op.id)?;
+            }
+            // The batch may have reached the history just before the crash.
+            journal.remove(batch.id())?;
+            batch.finish()?;
+            Ok(format!("Rolled back interrupted batch ({} operations)", applied.len()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OperationType;
+    use crate::transfer::copy_tree;
+    use crate::trash;
+    use tempfile::TempDir;
+
+    fn op(op_type: OperationType, source: &Path, destination: &Path) -> FileOperation {
+        FileOperation {
+            id: Uuid::new_v4().to_string(),
+            op_type,
+            source: Some(source.to_path_buf()),
+            destination: destination.to_path_buf(),
+            reason: "test".to_string(),
+            warnings: Vec::new(),
+        }
+    }
+
+    // Log `op` as started and drop the batch without closing it, like a killed process.
+    fn interrupt(config: &Path, op: &FileOperation) -> String {
+        let plan = Plan { operations: vec![op.clone()], summary: "test".to_string(), root: None };
+        let mut batch = WriteAheadLog::new(config).begin(&plan).unwrap();
+        batch.intent(op).unwrap();
+        batch.id().to_string()
+    }
+
+    fn recover_in(config: &Path, id: &str, action: RecoveryAction) -> Result<String, String> {
+        recover(&WriteAheadLog::new(config), &Journal::new(config), id, action)
+    }
+
+    #[test]
+    fn rolls_forward_an_op_that_never_started() {
+        let tmp = TempDir::new().unwrap();
+        let (src, dst) = (tmp.path().join("a.txt"), tmp.path().join("Documents/a.txt"));
+        fs::write(&src, "a").unwrap();
+        let op = op(OperationType::Move, &src, &dst);
+        let id = interrupt(tmp.path(), &op);
+
+        recover_in(tmp.path(), &id, RecoveryAction::RollForward).unwrap();
+        assert!(!src.exists());
+        assert_eq!(fs::read_to_string(&dst).unwrap(), "a");
+        assert_eq!(Journal::new(tmp.path()).entries().unwrap()[0].operations.len(), 1);
+        assert!(WriteAheadLog::new(tmp.path()).incomplete_batches().unwrap().is_empty());
+    }
+
+    #[test]
+    fn rolls_back_an_op_that_finished_before_the_crash() {
+        let tmp = TempDir::new().unwrap();
+        let (src, dst) = (tmp.path().join("a.txt"), tmp.path().join("b.txt"));
+        fs::write(&src, "a").unwrap();
+        let op = op(OperationType::Move, &src, &dst);
+        let id = interrupt(tmp.path(), &op);
+        fs::rename(&src, &dst).unwrap();
+
+        recover_in(tmp.path(), &id, RecoveryAction::RollBack).unwrap();
+        assert_eq!(fs::read_to_string(&src).unwrap(), "a");
+        assert!(!dst.exists());
+        assert!(Journal::new(tmp.path()).entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn finishes_a_cross_device_move_killed_before_removing_the_source() {
+        let tmp = TempDir::new().unwrap();
+        let (src, dst) = (tmp.path().join("project"), tmp.path().join("Projects/project"));
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("a.txt"), "a").unwrap();
+        fs::write(src.join("b.txt"), "b").unwrap();
+        let op = op(OperationType::Move, &src, &dst);
+        let id = interrupt(tmp.path(), &op);
+        fs::create_dir(dst.parent().unwrap()).unwrap();
+        copy_tree(&src, &dst).unwrap();
+        fs::remove_file(src.join("a.txt")).unwrap(); // Removal had started too
+
+        recover_in(tmp.path(), &id, RecoveryAction::RollForward).unwrap();
+        assert!(!src.exists());
+        assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(dst.join("b.txt")).unwrap(), "b");
+    }
+
+    #[test]
+    fn keeps_a_source_that_differs_from_the_destination() {
+        let tmp = TempDir::new().unwrap();
+        let (src, dst) = (tmp.path().join("a.txt"), tmp.path().join("b.txt"));
+        fs::write(&src, "mine").unwrap();
+        let op = op(OperationType::Move, &src, &dst);
+        let id = interrupt(tmp.path(), &op);
+        fs::write(&dst, "someone else's").unwrap();
+
+        assert!(recover_in(tmp.path(), &id, RecoveryAction::RollForward).is_err());
+        assert_eq!(fs::read_to_string(&src).unwrap(), "mine");
+        assert_eq!(fs::read_to_string(&dst).unwrap(), "someone else's");
+    }
+
+    #[test]
+    fn retries_a_trash_op_killed_after_writing_its_info_file() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("junk.tmp");
+        let dst = tmp.path().join("Trash/files/junk.tmp");
+        fs::write(&src, "junk").unwrap();
+        let op = op(OperationType::Trash, &src, &dst);
+        let id = interrupt(tmp.path(), &op);
+        trash::write_info(&src, &dst).unwrap();
+
+        recover_in(tmp.path(), &id, RecoveryAction::RollForward).unwrap();
+        assert!(!src.exists());
+        assert!(dst.exists());
+        assert!(trash::info_path(&dst).unwrap().exists());
+    }
+
+    #[test]
+    fn rolling_back_a_trash_op_leaves_no_info_file() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("junk.tmp");
+        let dst = tmp.path().join("Trash/files/junk.tmp");
+        fs::write(&src, "junk").unwrap();
+        let op = op(OperationType::Trash, &src, &dst);
+        let id = interrupt(tmp.path(), &op);
+        trash::write_info(&src, &dst).unwrap();
+
+        recover_in(tmp.path(), &id, RecoveryAction::RollBack).unwrap();
+        assert!(src.exists());
+        assert!(!trash::info_path(&dst).unwrap().exists());
+    }
+}
@@ -1,10 +1,12 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import type { FileItem, Project, Config, Plan, Operation } from "./types";
+import { listen } from "@tauri-apps/api/event";
+import type { FileItem, Project, Config, Plan, HistoryEntry, IncompleteBatch, ScanResult, ScanProgress, ReviewItem } from "./types";
 import { Overview } from "./components/dashboard/Overview";
 import { OperationsList } from "./components/operations/OperationsList";
 import { HistoryView } from "./components/dashboard/HistoryView";
 import { SettingsView } from "./components/dashboard/SettingsView";
+import { ReviewList } from "./components/dashboard/ReviewList";
 import { NeonButton } from "./components/ui/NeonButton";
 import { Sidebar } from "./components/layout/Sidebar";
 import { Topbar } from "./components/layout/Topbar";
@@ -16,11 +18,13 @@ function App() {
   const [rootPath, setRootPath] = useState("C:\\Users\\Yannick\\Desktop");
   const [config, setConfig] = useState<Config | null>(null);
   const [scanResults, setScanResults] = useState<{ files: FileItem[], projects: Project[] } | null>(null);
+  const [needsReview, setNeedsReview] = useState<ReviewItem[]>([]);
   const [plan, setPlan] = useState<Plan | null>(null);
   const [isScanning, setIsScanning] = useState(false);
+  const [scanProgress, setScanProgress] = useState<ScanProgress | null>(null);
   const [apiKey, setApiKey] = useState("");
   const [isAiProcessing, setIsAiProcessing] = useState(false);
-  const [history, setHistory] = useState<Operation[][]>([]);
+  const [history, setHistory] = useState<HistoryEntry[]>([]);
   const [searchQuery, setSearchQuery] = useState("");
 
   useEffect(() => {
@@ -33,12 +37,33 @@ function App() {
         const defaultConfig = await invoke<Config>("get_default_config");
         setConfig(defaultConfig);
       });
-    fetchHistory();
+    recoverInterruptedBatches().finally(fetchHistory);
   }, []);
 
+  // A previous apply was killed halfway: let the user finish it or put everything back.
+  const recoverInterruptedBatches = async () => {
+    try {
+      const batches = await invoke<IncompleteBatch[]>("get_incomplete_batches");
+      for (const batch of batches) {
+        const done = batch.completed.length;
+        const left = batch.pending.length + (batch.in_flight ? 1 : 0);
+        const forward = confirm(
+          `An organize run from ${new Date(batch.started).toLocaleString()} was interrupted (${done} done, ${left} left).\n\n` +
+          `OK: finish the remaining operations\nCancel: undo the completed ones`
+        );
+        await invoke<string>("recover_incomplete_batch", {
+          batchId: batch.id,
+          action: forward ? "RollForward" : "RollBack",
+        });
+      }
+    } catch (e) {
+      console.error("Failed to recover interrupted batch:", e);
+    }
+  };
+
   const fetchHistory = async () => {
     try {
-      const hist = await invoke<Operation[][]>("get_history");
+      const hist = await invoke<HistoryEntry[]>("get_history");
       setHistory(hist);
     } catch (e) {
       console.error("Failed to fetch history:", e);
@@ -48,9 +73,13 @@ function App() {
   const handleScan = async () => {
     if (!config) return;
     setIsScanning(true);
+    setScanProgress(null);
+    const unlisten = await listen<ScanProgress>("scan-progress", (event) => setScanProgress(event.payload));
     try {
-      const [files, projects] = await invoke<[FileItem[], Project[]]>("scan_directory", { path: rootPath, config });
+      const { files, projects, cancelled, needs_review } = await invoke<ScanResult>("scan_directory", { path: rootPath, config });
+      if (cancelled) console.warn("Scan cancelled, showing partial results");
       setScanResults({ files, projects });
+      setNeedsReview(needs_review);
 
       const generatedPlan = await invoke<Plan>("create_plan", { files, projects, config, root: rootPath });
       setPlan(generatedPlan);
@@ -58,7 +87,40 @@ function App() {
     } catch (e) {
       console.error(`Error: ${e}`);
     } finally {
+      unlisten();
       setIsScanning(false);
+      setScanProgress(null);
+    }
+  };
+
+  const handleCancelScan = async () => {
+    try {
+      await invoke("cancel_scan");
+    } catch (e) {
+      console.error(`Error cancelling scan: ${e}`);
+    }
+  };
+
+  // Remember the answer for future scans; a confirmed project joins this plan right away.
+  const handleReview = async (item: ReviewItem, isProject: boolean) => {
+    if (!config || !scanResults) return;
+    const path = item.project.path;
+    const newConfig = {
+      ...config,
+      confirmed_projects: [...config.confirmed_projects.filter(p => p !== path), ...(isProject ? [path] : [])],
+      rejected_projects: [...config.rejected_projects.filter(p => p !== path), ...(isProject ? [] : [path])],
+    };
+    setConfig(newConfig);
+    setNeedsReview(needsReview.filter(r => r.project.path !== path));
+    try {
+      await invoke("save_config_file", { config: newConfig });
+      if (isProject) {
+        const projects = [...scanResults.projects, item.project];
+        setScanResults({ ...scanResults, projects });
+        setPlan(await invoke<Plan>("create_plan", { files: scanResults.files, projects, config: newConfig, root: rootPath }));
+      }
+    } catch (e) {
+      console.error(`Error saving review decision: ${e}`);
     }
   };
 
@@ -68,8 +130,10 @@ function App() {
 
     try {
       await invoke<string>("apply_plan", { plan });
+      fetchHistory();
       setPlan(null);
       setScanResults(null);
+      setNeedsReview([]);
       setActiveTab("dashboard");
     } catch (e) {
       console.error(`Error applying: ${e}`);
@@ -134,7 +198,9 @@ function App() {
               rootPath={rootPath}
               setRootPath={setRootPath}
               onScan={handleScan}
+              onCancelScan={handleCancelScan}
               isScanning={isScanning}
+              scanProgress={scanProgress}
               stats={scanResults ? { files: scanResults.files.length, projects: scanResults.projects.length } : null}
             />
           )}
@@ -199,6 +265,8 @@ function App() {
                 </div>
               </div>
 
+              <ReviewList items={needsReview} onDecide={handleReview} />
+
               {plan ? (
                 <OperationsList
                   operations={filteredOperations || plan.operations}
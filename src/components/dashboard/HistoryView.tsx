@@ -2,10 +2,10 @@ import { motion } from "framer-motion";
 import { Clock, Undo2, CheckCircle2 } from "lucide-react";
 import { GlassCard } from "../ui/GlassCard";
 import { NeonButton } from "../ui/NeonButton";
-import type { Operation } from "../../types";
+import type { HistoryEntry } from "../../types";
 
 interface HistoryViewProps {
-    history: Operation[][];
+    history: HistoryEntry[];
     onUndo?: () => void;
 }
 
@@ -31,12 +31,12 @@ export const HistoryView: React.FC<HistoryViewProps> = ({ history, onUndo }) =>
                 {history.length === 0 ? (
                     <div className="text-center py-12 text-zinc-500 bg-zinc-900/20 rounded-xl border border-zinc-800/50">
                         <Clock size={48} className="mx-auto mb-4 opacity-20" />
-                        <p>No operations recorded in this session.</p>
+                        <p>No operations recorded yet.</p>
                     </div>
                 ) : (
-                    history.slice().reverse().map((batch, i) => (
+                    history.slice().reverse().map((entry, i) => (
                         <motion.div
-                            key={i}
+                            key={entry.id}
                             initial={{ opacity: 0, x: -10 }}
                             animate={{ opacity: 1, x: 0 }}
                             transition={{ delay: i * 0.1 }}
@@ -48,7 +48,9 @@ export const HistoryView: React.FC<HistoryViewProps> = ({ history, onUndo }) =>
                                     </div>
                                     <div className="flex-1">
                                         <div className="text-zinc-200 font-medium">Batch Operation #{history.length - i}</div>
-                                        <div className="text-xs text-zinc-500">{batch.length} files affected</div>
+                                        <div className="text-xs text-zinc-500">
+                                            {entry.operations.length} files affected · {new Date(entry.timestamp).toLocaleString()}
+                                        </div>
                                     </div>
                                     {i === 0 && (
                                         <div className="px-2 py-0.5 rounded-full bg-zinc-800 text-zinc-400 text-xs font-medium border border-zinc-700">
@@ -57,7 +59,7 @@ export const HistoryView: React.FC<HistoryViewProps> = ({ history, onUndo }) =>
                                     )}
                                 </div>
                                 <div className="space-y-1 pl-14">
-                                    {batch.slice(0, 3).map((op, opIdx) => (
+                                    {entry.operations.slice(0, 3).map((op, opIdx) => (
                                         <div key={opIdx} className="text-xs text-zinc-400 flex items-center gap-2">
                                             <span className={`w-1.5 h-1.5 rounded-full ${op.op_type === 'Move' ? 'bg-white' : 'bg-zinc-500'}`} />
                                             <span className="truncate max-w-md">
@@ -68,9 +70,9 @@ export const HistoryView: React.FC<HistoryViewProps> = ({ history, onUndo }) =>
                                             </span>
                                         </div>
                                     ))}
-                                    {batch.length > 3 && (
+                                    {entry.operations.length > 3 && (
                                         <div className="text-xs text-zinc-500 italic">
-                                            + {batch.length - 3} more operations...
+                                            + {entry.operations.length - 3} more operations...
                                         </div>
                                     )}
                                 </div>
@@ -1,12 +1,15 @@
 import { motion } from "framer-motion";
 import { FolderSearch, Play, FileText, Database, FileCode, Image, Video, File, Bot } from "lucide-react";
 import { GlassCard } from "../ui/GlassCard";
+import type { ScanProgress } from "../../types";
 
 interface OverviewProps {
     rootPath: string;
     setRootPath: (path: string) => void;
     onScan: () => void;
+    onCancelScan: () => void;
     isScanning: boolean;
+    scanProgress: ScanProgress | null;
     stats: { files: number; projects: number } | null;
 }
 
@@ -35,7 +38,9 @@ export const Overview: React.FC<OverviewProps> = ({
     rootPath,
     setRootPath,
     onScan,
+    onCancelScan,
     isScanning,
+    scanProgress,
     stats
 }) => {
     return (
@@ -107,6 +112,27 @@ export const Overview: React.FC<OverviewProps> = ({
                                     )}
                                 </div>
                             </button>
+
+                            {isScanning && (
+                                <div className="flex items-center justify-between gap-4 text-xs text-zinc-400 font-mono">
+                                    <div className="min-w-0">
+                                        {scanProgress ? (
+                                            <>
+                                                <div>{scanProgress.dirs_visited} folders · {scanProgress.files_found} files · {scanProgress.projects_found} projects</div>
+                                                <div className="truncate text-zinc-500" title={scanProgress.current_path}>{scanProgress.current_path}</div>
+                                            </>
+                                        ) : (
+                                            <div>Starting...</div>
+                                        )}
+                                    </div>
+                                    <button
+                                        onClick={onCancelScan}
+                                        className="shrink-0 px-4 py-2 rounded-lg border border-zinc-700/50 text-zinc-300 hover:text-white hover:border-red-500/50 transition-colors uppercase tracking-wider"
+                                    >
+                                        Cancel
+                                    </button>
+                                </div>
+                            )}
                         </GlassCard>
                     </div>
 
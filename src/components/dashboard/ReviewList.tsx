@@ -0,0 +1,44 @@
+import { HelpCircle } from "lucide-react";
+import { GlassCard } from "../ui/GlassCard";
+import { NeonButton } from "../ui/NeonButton";
+import type { ReviewItem } from "../../types";
+
+interface ReviewListProps {
+    items: ReviewItem[];
+    onDecide: (item: ReviewItem, isProject: boolean) => void;
+}
+
+// Folders that only weakly look like projects. They are left alone until decided.
+export const ReviewList: React.FC<ReviewListProps> = ({ items, onDecide }) => {
+    if (items.length === 0) return null;
+
+    return (
+        <GlassCard className="space-y-3 border-amber-500/30 bg-black/40">
+            <div className="flex items-center gap-2 text-amber-200">
+                <HelpCircle size={18} />
+                <h3 className="font-semibold">Needs review ({items.length})</h3>
+            </div>
+            <p className="text-sm text-zinc-400">
+                These folders might be projects. They will not be moved until you decide, and your answer is remembered.
+            </p>
+            {items.map((item) => (
+                <div key={item.project.path} className="flex items-center justify-between gap-4 py-2 border-t border-zinc-800">
+                    <div className="min-w-0">
+                        <div className="font-mono text-sm text-zinc-200 truncate" title={item.project.path}>{item.project.path}</div>
+                        <div className="text-xs text-zinc-500">
+                            {item.reason} · confidence {item.confidence.toFixed(2)}
+                        </div>
+                    </div>
+                    <div className="flex gap-2 shrink-0">
+                        <NeonButton size="sm" variant="primary" onClick={() => onDecide(item, true)}>
+                            Project
+                        </NeonButton>
+                        <NeonButton size="sm" variant="secondary" onClick={() => onDecide(item, false)}>
+                            Not a project
+                        </NeonButton>
+                    </div>
+                </div>
+            ))}
+        </GlassCard>
+    );
+};
@@ -1,5 +1,5 @@
 import { motion } from "framer-motion";
-import { FolderInput, FileOutput, Trash2, Ban, ArrowRight, ChevronDown, File, Folder, Rocket, Sparkles, Scale, FileType, Info, X } from "lucide-react";
+import { FolderInput, FileOutput, Trash2, Ban, ArrowRight, ChevronDown, File, Folder, Rocket, Sparkles, Scale, FileType, Info, X, AlertTriangle } from "lucide-react";
 import { useState } from "react";
 import { GlassCard } from "../ui/GlassCard";
 import { cn } from "../../lib/utils";
@@ -97,7 +97,12 @@ export const OperationCard: React.FC<OperationCardProps> = ({ operation, index,
                         </div>
 
                         {/* Reason Pill */}
-                        <div className="col-span-3 flex justify-end">
+                        <div className="col-span-3 flex justify-end items-center gap-2">
+                            {operation.warnings && operation.warnings.length > 0 && (
+                                <span title={operation.warnings.join("\n")}>
+                                    <AlertTriangle size={16} className="text-amber-400" />
+                                </span>
+                            )}
                             <span className={cn("px-2.5 py-1 rounded-full text-xs font-medium border uppercase tracking-wider flex items-center gap-1.5", getBadgeColor())}>
                                 <ReasonIcon size={12} strokeWidth={2.5} />
                                 <span className="truncate max-w-[120px]" title={reasonLabel}>{reasonLabel}</span>
@@ -147,6 +152,14 @@ export const OperationCard: React.FC<OperationCardProps> = ({ operation, index,
                                 {operation.destination}
                             </code>
                         </div>
+                        {operation.warnings && operation.warnings.length > 0 && (
+                            <div className="col-span-2">
+                                <span className="text-zinc-500 block mb-1">Warnings</span>
+                                <ul className="space-y-1 text-amber-300">
+                                    {operation.warnings.map((w) => <li key={w}>{w}</li>)}
+                                </ul>
+                            </div>
+                        )}
                     </div>
                 </motion.div>
             </GlassCard>
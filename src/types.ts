@@ -12,11 +12,32 @@ export interface Project {
     name: string;
     type_guess: string;
     internal_name?: string;
+    metadata?: ProjectMetadata; // Absent when collect_project_metadata is off
+}
+
+export interface ProjectMetadata {
+    size: number; // bytes, without ignored folders and build output
+    last_modified: number; // Unix seconds
+    last_commit?: number; // Unix seconds
+    languages: LanguageShare[]; // Largest first
+    build_artifacts: string[]; // e.g. "target", "web/node_modules"
+}
+
+export interface LanguageShare {
+    language: string;
+    files: number;
+    bytes: number;
 }
 
 export interface Config {
     ignore_patterns: string[];
     project_markers: string[];
+    min_project_confidence: number;
+    confirmed_projects: string[];
+    rejected_projects: string[];
+    refuse_dirty_repos: boolean;
+    refuse_linked_repos: boolean;
+    collect_project_metadata: boolean;
 }
 
 export interface Plan {
@@ -26,8 +47,47 @@ export interface Plan {
 
 export interface Operation {
     id: string; // generated
-    op_type: "Move" | "CreateDir" | "Copy" | "Delete" | "Ignore";
+    op_type: "Move" | "CreateDir" | "Copy" | "Delete" | "Trash" | "HardLink" | "Ignore";
     source?: string;
     destination: string;
     reason: string;
+    warnings?: string[]; // e.g. uncommitted changes in a repository being moved
+}
+
+export interface HistoryEntry {
+    id: string;
+    timestamp: string; // ISO 8601, UTC
+    root?: string;
+    summary: string;
+    operations: Operation[];
+}
+
+export interface IncompleteBatch {
+    id: string;
+    started: string;
+    root?: string;
+    summary: string;
+    completed: Operation[];
+    in_flight?: Operation;
+    pending: Operation[];
+}
+
+export interface ScanResult {
+    files: FileItem[];
+    projects: Project[];
+    cancelled: boolean; // Stopped early; the lists are partial
+    needs_review: ReviewItem[];
+}
+
+export interface ReviewItem {
+    project: Project;
+    confidence: number;
+    reason: string;
+}
+
+export interface ScanProgress {
+    dirs_visited: number;
+    files_found: number;
+    projects_found: number;
+    current_path: string;
 }